@@ -0,0 +1,56 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactCache', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should share the cache of a resource type within a render', async () => {
+    const createCache = jest.fn(() => new Map())
+    const caches = []
+
+    function Reader() {
+      caches.push(React.unstable_getCacheForType(createCache))
+      return <span />
+    }
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return (
+        <div>
+          {count}
+          <Reader />
+          <Reader />
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(createCache).toHaveBeenCalledTimes(1)
+    expect(caches.length).toBe(2)
+    expect(caches[0]).toBe(caches[1])
+
+    // the cache is released by the commit, the next render starts with its own
+    setCount(1)
+    await sleep(10)
+    expect(createCache).toHaveBeenCalledTimes(2)
+    expect(caches.length).toBe(4)
+    expect(caches[2]).toBe(caches[3])
+    expect(caches[2]).not.toBe(caches[0])
+  })
+})
@@ -0,0 +1,52 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMBatchedUpdates', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should return the result of the callback and render the batch once', async () => {
+    let renderCount = 0
+    let setA
+    let setB
+    function App() {
+      const [a, _setA] = React.useState(0)
+      const [b, _setB] = React.useState(0)
+      setA = _setA
+      setB = _setB
+      renderCount++
+      return (
+        <div>
+          {a}-{b}
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(renderCount).toBe(1)
+
+    const result = ReactDOM.unstable_batchedUpdates((value) => {
+      setA(value)
+      setB(value * 2)
+      return 'done ' + value
+    }, 1)
+    expect(result).toBe('done 1')
+
+    await sleep(10)
+    expect(el.textContent).toBe('1-2')
+    expect(renderCount).toBe(2)
+  })
+})
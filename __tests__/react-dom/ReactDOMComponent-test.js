@@ -0,0 +1,194 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMComponent', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  afterEach(() => {
+    document.body.innerHTML = ''
+  })
+
+  it('should focus an input with autoFocus after it is mounted', async () => {
+    const el = document.createElement('div')
+    document.body.appendChild(el)
+
+    let isConnectedWhenFocused
+    function onFocus(e) {
+      isConnectedWhenFocused = e.target.isConnected
+    }
+
+    ReactDOM.createRoot(el).render(
+      <div>
+        <input />
+        <input autoFocus={true} />
+      </div>
+    )
+    const inputs = () => el.querySelectorAll('input')
+    document.addEventListener('focusin', onFocus)
+    await sleep(10)
+    document.removeEventListener('focusin', onFocus)
+
+    expect(document.activeElement).toBe(inputs()[1])
+    expect(isConnectedWhenFocused).toBe(true)
+  })
+
+  it('should not focus again when an autoFocus input updates', async () => {
+    const el = document.createElement('div')
+    document.body.appendChild(el)
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return (
+        <div>
+          <input autoFocus={true} />
+          <button>{count}</button>
+        </div>
+      )
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(document.activeElement).toBe(el.querySelector('input'))
+
+    el.querySelector('button').focus()
+    setCount(1)
+    await sleep(10)
+    expect(document.activeElement).toBe(el.querySelector('button'))
+  })
+
+  it('should create a text node for each text child', async () => {
+    const el = document.createElement('div')
+
+    let setName
+    function App() {
+      const [name, _setName] = React.useState('a')
+      setName = _setName
+      return (
+        <div>
+          {'hello '}
+          {name}
+        </div>
+      )
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const div = el.querySelector('div')
+    expect(div.childNodes.length).toBe(2)
+    expect(
+      Array.from(div.childNodes).map((node) => node.nodeType)
+    ).toEqual([Node.TEXT_NODE, Node.TEXT_NODE])
+    expect(div.textContent).toBe('hello a')
+
+    const textNode = div.childNodes[1]
+    setName('b')
+    await sleep(10)
+    expect(div.childNodes[1]).toBe(textNode)
+    expect(div.textContent).toBe('hello b')
+  })
+
+  it('should update the value property of a controlled input', async () => {
+    const el = document.createElement('div')
+
+    let setValue
+    function App() {
+      const [value, _setValue] = React.useState('a')
+      setValue = _setValue
+      return <input value={value} onChange={() => {}} />
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const input = el.querySelector('input')
+    expect(input.value).toBe('a')
+
+    setValue('b')
+    await sleep(10)
+    expect(el.querySelector('input')).toBe(input)
+    expect(input.value).toBe('b')
+    // the value is a property, not an attribute
+    expect(input.getAttribute('value')).toBe(null)
+  })
+
+  it('should only apply defaultValue on mount', async () => {
+    const el = document.createElement('div')
+
+    let setDefaultValue
+    function App() {
+      const [defaultValue, _setDefaultValue] = React.useState('a')
+      setDefaultValue = _setDefaultValue
+      return <input defaultValue={defaultValue} />
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const input = el.querySelector('input')
+    expect(input.value).toBe('a')
+
+    input.value = 'typed'
+    setDefaultValue('b')
+    await sleep(10)
+    expect(input.value).toBe('typed')
+    expect(input.defaultValue).toBe('a')
+  })
+
+  it('should set the checked property of a controlled checkbox', async () => {
+    const el = document.createElement('div')
+
+    let setChecked
+    function App() {
+      const [checked, _setChecked] = React.useState(false)
+      setChecked = _setChecked
+      return <input type="checkbox" checked={checked} onChange={() => {}} />
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const input = el.querySelector('input')
+    expect(input.type).toBe('checkbox')
+    expect(input.checked).toBe(false)
+
+    setChecked(true)
+    await sleep(10)
+    expect(input.checked).toBe(true)
+  })
+
+  it('should reuse the DOM node of an updated host component', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <div className='a'>
+        <span>text</span>
+      </div>
+    )
+    await sleep(10)
+    const div = el.firstChild
+    const span = div.firstChild
+
+    root.render(
+      <div className='b' title='updated'>
+        <span>text</span>
+      </div>
+    )
+    await sleep(10)
+    expect(el.firstChild).toBe(div)
+    expect(div.firstChild).toBe(span)
+    expect(el.innerHTML).toBe(
+      '<div class="b" title="updated"><span>text</span></div>'
+    )
+  })
+})
@@ -0,0 +1,46 @@
+'use strict'
+
+let ReactDOM
+
+describe('ReactDOMFloat', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  afterEach(() => {
+    document.head.innerHTML = ''
+  })
+
+  it('should insert a module script once per href with preinitModule', () => {
+    ReactDOM.preinitModule('/a.js')
+    ReactDOM.preinitModule('/a.js', {as: 'script'})
+    ReactDOM.preinitModule('/b.js', {integrity: 'sha-b'})
+
+    const scripts = document.head.querySelectorAll('script[type="module"]')
+    expect(scripts.length).toBe(2)
+    expect(scripts[0].getAttribute('src')).toBe('/a.js')
+    expect(scripts[1].getAttribute('src')).toBe('/b.js')
+    expect(scripts[1].getAttribute('integrity')).toBe('sha-b')
+  })
+
+  it('should ignore preinitModule for anything but scripts', () => {
+    ReactDOM.preinitModule('/a.json', {as: 'json'})
+    expect(document.head.querySelectorAll('script').length).toBe(0)
+  })
+
+  it('should insert a modulepreload link once per href with preloadModule', () => {
+    ReactDOM.preloadModule('/a.js')
+    ReactDOM.preloadModule('/a.js')
+
+    const links = document.head.querySelectorAll('link[rel="modulepreload"]')
+    expect(links.length).toBe(1)
+    expect(links[0].getAttribute('href')).toBe('/a.js')
+  })
+
+  it('should not preload a module that is already initialized', () => {
+    ReactDOM.preinitModule('/a.js')
+    ReactDOM.preloadModule('/a.js')
+    expect(document.head.querySelectorAll('link').length).toBe(0)
+  })
+})
@@ -0,0 +1,193 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMFlushSync', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  afterEach(() => {
+    jest.restoreAllMocks()
+  })
+
+  it('should flush updates synchronously', async () => {
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return <div>{count}</div>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('0')
+
+    ReactDOM.flushSync(() => setCount(1))
+    expect(el.textContent).toBe('1')
+  })
+
+  it('should warn and defer when called during the commit phase', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    let commitCount = 0
+    function App() {
+      const [count, setCount] = React.useState(0)
+      return (
+        <div
+          ref={(dom) => {
+            if (dom !== null) {
+              commitCount++
+              if (count === 0) {
+                ReactDOM.flushSync(() => setCount(1))
+                // the update is not flushed inside the current commit
+                expect(dom.textContent).toBe('0')
+              }
+            }
+          }}>
+          {count}
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    expect(consoleError).toHaveBeenCalledWith(
+      expect.stringContaining('flushSync was called from inside a lifecycle method')
+    )
+    expect(el.textContent).toBe('1')
+    expect(commitCount).toBe(2)
+  })
+
+  it('should flush an update scheduled in the layout phase before returning', () => {
+    const log = []
+    function App() {
+      const [width, setWidth] = React.useState(0)
+      log.push('render ' + width)
+      return (
+        <div
+          ref={(dom) => {
+            if (dom !== null && width === 0) {
+              setWidth(10)
+            }
+          }}>
+          {width}
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    ReactDOM.flushSync(() => root.render(<App />))
+    // the second render is committed before the browser gets a chance to paint
+    expect(log).toEqual(['render 0', 'render 10'])
+    expect(el.textContent).toBe('10')
+  })
+
+  it('should flush the sync work scheduled while flushing before returning', () => {
+    let setB
+    function B() {
+      const [b, _setB] = React.useState('B0')
+      setB = _setB
+      return <span>{b}</span>
+    }
+
+    function A(props) {
+      return (
+        <span
+          ref={(dom) => {
+            if (dom !== null && props.text === 'A1') {
+              setB('B1')
+            }
+          }}>
+          {props.text}
+        </span>
+      )
+    }
+
+    const elA = document.createElement('div')
+    const elB = document.createElement('div')
+    const rootA = ReactDOM.createRoot(elA)
+    const rootB = ReactDOM.createRoot(elB)
+    ReactDOM.flushSync(() => {
+      rootA.render(<A text='A0' />)
+      rootB.render(<B />)
+    })
+    expect(elA.textContent).toBe('A0')
+    expect(elB.textContent).toBe('B0')
+
+    // the commit of root A schedules a sync update of root B
+    ReactDOM.flushSync(() => rootA.render(<A text='A1' />))
+    expect(elA.textContent).toBe('A1')
+    expect(elB.textContent).toBe('B1')
+  })
+
+  it('should stop an update loop in the layout phase', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    let renders = 0
+    function Loop() {
+      const [count, setCount] = React.useState(0)
+      renders++
+      return (
+        <div
+          ref={(dom) => {
+            if (dom !== null) {
+              setCount((c) => c + 1)
+            }
+          }}>
+          {count}
+        </div>
+      )
+    }
+
+    let setLabel
+    function Label() {
+      const [label, _setLabel] = React.useState('a')
+      setLabel = _setLabel
+      return <span>{label}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <div>
+        <Loop />
+        <Label />
+      </div>
+    )
+    await sleep(10)
+    expect(consoleError).toHaveBeenCalledTimes(1)
+    expect(consoleError).toHaveBeenCalledWith(
+      expect.stringContaining('Maximum update depth exceeded')
+    )
+
+    // no more renders are scheduled once the loop is stopped
+    const rendersAfterError = renders
+    await sleep(10)
+    expect(renders).toBe(rendersAfterError)
+
+    // the dropped update isn't rendered with a later sync update of the root
+    ReactDOM.flushSync(() => setLabel('b'))
+    await sleep(10)
+    expect(el.querySelector('span').textContent).toBe('b')
+    expect(renders).toBe(rendersAfterError)
+    expect(consoleError).toHaveBeenCalledTimes(1)
+    consoleError.mockRestore()
+  })
+})
@@ -0,0 +1,54 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMLegacyRender', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should call the render callback after the initial mount commits', async () => {
+    const log = []
+    function App() {
+      log.push('render')
+      return <div>hello</div>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.render(<App />, el, () => {
+      log.push('callback ' + el.innerHTML)
+    })
+    await sleep(10)
+    expect(log).toEqual(['render', 'callback <div>hello</div>'])
+  })
+
+  it('should reuse the root when rendering into the same container', async () => {
+    const el = document.createElement('div')
+    ReactDOM.render(<span>a</span>, el)
+    await sleep(10)
+    const span = el.querySelector('span')
+
+    let callbackContent
+    ReactDOM.render(<span>b</span>, el, () => {
+      callbackContent = el.textContent
+    })
+    await sleep(10)
+    expect(el.querySelector('span')).toBe(span)
+    expect(callbackContent).toBe('b')
+  })
+
+  it('should throw when the container is not a DOM element', () => {
+    expect(() => ReactDOM.render(<div />, null)).toThrow(
+      'render(...): Target container is not a DOM element.'
+    )
+  })
+})
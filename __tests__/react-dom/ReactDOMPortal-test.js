@@ -0,0 +1,83 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMPortal', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should render the children into the portal container', async () => {
+    const el = document.createElement('div')
+    const portalContainer = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>{ReactDOM.createPortal(<span>portal</span>, portalContainer)}</section>
+    )
+    await sleep(10)
+    expect(el.innerHTML).toBe('<section></section>')
+    expect(portalContainer.innerHTML).toBe('<span>portal</span>')
+
+    root.render(<section />)
+    await sleep(10)
+    expect(portalContainer.innerHTML).toBe('')
+  })
+
+  it('should reorder keyed portals by key', async () => {
+    const el = document.createElement('div')
+    const containerA = document.createElement('div')
+    const containerB = document.createElement('div')
+
+    let mountCount = 0
+    function Stateful(props) {
+      const [id] = React.useState(() => ++mountCount)
+      return <span>{props.name + id}</span>
+    }
+
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        {[
+          ReactDOM.createPortal(<Stateful name='a' />, containerA, 'a'),
+          ReactDOM.createPortal(<Stateful name='b' />, containerB, 'b')
+        ]}
+      </section>
+    )
+    await sleep(10)
+    expect(containerA.innerHTML).toBe('<span>a1</span>')
+    expect(containerB.innerHTML).toBe('<span>b2</span>')
+    const spanA = containerA.querySelector('span')
+    const spanB = containerB.querySelector('span')
+
+    root.render(
+      <section>
+        {[
+          ReactDOM.createPortal(<Stateful name='b' />, containerB, 'b'),
+          ReactDOM.createPortal(<Stateful name='a' />, containerA, 'a')
+        ]}
+      </section>
+    )
+    await sleep(10)
+    // the portal fibers are matched by key, so the children keep their state
+    expect(containerA.innerHTML).toBe('<span>a1</span>')
+    expect(containerB.innerHTML).toBe('<span>b2</span>')
+    expect(containerA.querySelector('span')).toBe(spanA)
+    expect(containerB.querySelector('span')).toBe(spanB)
+    expect(mountCount).toBe(2)
+  })
+
+  it('should throw when the container is not a DOM element', () => {
+    expect(() => ReactDOM.createPortal(<div />, null)).toThrow(
+      'createPortal(...): Target container is not a DOM element.'
+    )
+  })
+})
@@ -0,0 +1,90 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMRef', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should detach the old ref and attach the new one when the ref changes', async () => {
+    const log = []
+    const refA = (node) => log.push(['A', node])
+    const refB = (node) => log.push(['B', node])
+
+    let setUseB
+    function App() {
+      const [useB, _setUseB] = React.useState(false)
+      setUseB = _setUseB
+      return <div ref={useB ? refB : refA} />
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const div = el.querySelector('div')
+    expect(log).toEqual([['A', div]])
+
+    log.length = 0
+    setUseB(true)
+    await sleep(10)
+    expect(el.querySelector('div')).toBe(div)
+    expect(log).toEqual([
+      ['A', null],
+      ['B', div]
+    ])
+  })
+
+  it('should move an object ref to the new ref object', async () => {
+    const refA = {current: null}
+    const refB = {current: null}
+
+    let setUseB
+    function App() {
+      const [useB, _setUseB] = React.useState(false)
+      setUseB = _setUseB
+      return <div ref={useB ? refB : refA} />
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const div = el.querySelector('div')
+    expect(refA.current).toBe(div)
+
+    setUseB(true)
+    await sleep(10)
+    expect(refA.current).toBe(null)
+    expect(refB.current).toBe(div)
+  })
+
+  it('should not touch the ref when it does not change', async () => {
+    const log = []
+    const ref = (node) => log.push(node)
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return <div ref={ref}>{count}</div>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(log.length).toBe(1)
+
+    setCount(1)
+    await sleep(10)
+    expect(log.length).toBe(1)
+  })
+})
@@ -0,0 +1,173 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMRoot', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  function createTree(log) {
+    function Effect(props) {
+      React.useEffect(() => {
+        log.push('mount ' + props.name)
+        return () => log.push('unmount ' + props.name)
+      }, [])
+      return <span>{props.name}</span>
+    }
+    const MemoEffect = React.memo(Effect)
+
+    return function App() {
+      React.useEffect(() => {
+        log.push('mount App')
+        return () => log.push('unmount App')
+      }, [])
+      return (
+        <div>
+          <Effect name='A' />
+          <MemoEffect name='B' />
+        </div>
+      )
+    }
+  }
+
+  it('should run all passive effect cleanups when unmounting the root', async () => {
+    const log = []
+    const App = createTree(log)
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<App />)
+    await sleep(10)
+    expect(log.sort()).toEqual(['mount A', 'mount App', 'mount B'])
+
+    log.length = 0
+    root.unmount()
+    expect(el.innerHTML).toBe('')
+    expect(log.sort()).toEqual(['unmount A', 'unmount App', 'unmount B'])
+  })
+
+  it('should flush pending passive effects before unmounting', async () => {
+    const log = []
+    const App = createTree(log)
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<App />)
+    // committed in a micro task, the passive effects are not flushed yet
+    await Promise.resolve()
+    expect(el.textContent).toBe('AB')
+
+    root.unmount()
+    expect(log.slice(0, 3).sort()).toEqual(['mount A', 'mount App', 'mount B'])
+    expect(log.slice(3).sort()).toEqual(['unmount A', 'unmount App', 'unmount B'])
+
+    await sleep(10)
+    expect(log.length).toBe(6)
+  })
+
+  it('should clear the tree when rendering null and keep the root', async () => {
+    const log = []
+    const App = createTree(log)
+    const el = document.createElement('div')
+    document.body.appendChild(el)
+    const root = ReactDOM.createRoot(el)
+    root.render(<App />)
+    await sleep(10)
+
+    log.length = 0
+    root.render(null)
+    await sleep(10)
+    expect(el.innerHTML).toBe('')
+    expect(log.sort()).toEqual(['unmount A', 'unmount App', 'unmount B'])
+
+    let clicks = 0
+    root.render(<button onClick={() => clicks++}>click</button>)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<button>click</button>')
+
+    // the root didn't attach its listeners once more for the second render
+    el.querySelector('button').click()
+    expect(clicks).toBe(1)
+    document.body.removeChild(el)
+  })
+
+  it('should throw when the container is not a DOM element', () => {
+    expect(() => ReactDOM.createRoot(null)).toThrow(
+      'createRoot(...): Target container is not a DOM element.'
+    )
+    expect(() => ReactDOM.createRoot(document.createTextNode('text'))).toThrow(
+      'createRoot(...): Target container is not a DOM element.'
+    )
+  })
+
+  it('should update two roots independently', async () => {
+    const setters = {}
+    const renders = []
+    function Counter(props) {
+      const [count, setCount] = React.useState(0)
+      setters[props.name] = setCount
+      renders.push(props.name + count)
+      return (
+        <button onClick={() => setCount((c) => c + 1)}>
+          {props.name + count}
+        </button>
+      )
+    }
+
+    const elA = document.createElement('div')
+    const elB = document.createElement('div')
+    document.body.appendChild(elA)
+    document.body.appendChild(elB)
+    ReactDOM.createRoot(elA).render(<Counter name='A' />)
+    ReactDOM.createRoot(elB).render(<Counter name='B' />)
+    await sleep(10)
+    expect(renders.sort()).toEqual(['A0', 'B0'])
+
+    renders.length = 0
+    setters.A(1)
+    await sleep(10)
+    expect(elA.textContent).toBe('A1')
+    expect(elB.textContent).toBe('B0')
+    expect(renders).toEqual(['A1'])
+
+    renders.length = 0
+    elB.querySelector('button').click()
+    await sleep(10)
+    expect(elA.textContent).toBe('A1')
+    expect(elB.textContent).toBe('B1')
+    expect(renders).toEqual(['B1'])
+
+    document.body.removeChild(elA)
+    document.body.removeChild(elB)
+  })
+
+  it('should dispatch an event once to a root rendered inside another', async () => {
+    const log = []
+    const outer = document.createElement('div')
+    document.body.appendChild(outer)
+    ReactDOM.createRoot(outer).render(
+      <div onClick={() => log.push('outer')}>
+        <section />
+      </div>
+    )
+    await sleep(10)
+
+    const inner = outer.querySelector('section')
+    ReactDOM.createRoot(inner).render(
+      <button onClick={() => log.push('inner')}>click</button>
+    )
+    await sleep(10)
+
+    inner.querySelector('button').click()
+    expect(log).toEqual(['inner', 'outer'])
+    document.body.removeChild(outer)
+  })
+})
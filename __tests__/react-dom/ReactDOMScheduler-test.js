@@ -0,0 +1,56 @@
+'use strict'
+
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+const NormalPriority = 3
+
+describe('ReactDOMScheduler', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should run the scheduled callbacks that were not cancelled', async () => {
+    const log = []
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => log.push('A'))
+    const task = ReactDOM.unstable_scheduleCallback(NormalPriority, () =>
+      log.push('B')
+    )
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => log.push('C'))
+    ReactDOM.unstable_cancelCallback(task)
+    await sleep(10)
+    expect(log).toEqual(['A', 'C'])
+  })
+
+  it('should run a delayed callback after the delay', async () => {
+    const log = []
+    const start = ReactDOM.unstable_now()
+    ReactDOM.unstable_scheduleCallback(
+      NormalPriority,
+      () => log.push(ReactDOM.unstable_now() - start),
+      {delay: 20}
+    )
+    await sleep(5)
+    expect(log).toEqual([])
+    await sleep(40)
+    expect(log.length).toBe(1)
+    expect(log[0]).toBeGreaterThanOrEqual(20)
+  })
+
+  it('should keep running the callbacks after a paint is requested', async () => {
+    const log = []
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => {
+      log.push('A')
+      ReactDOM.unstable_requestPaint()
+    })
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => log.push('B'))
+    await sleep(10)
+    expect(log).toEqual(['A', 'B'])
+  })
+})
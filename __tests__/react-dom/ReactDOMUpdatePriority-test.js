@@ -0,0 +1,93 @@
+'use strict'
+
+let React
+let ReactDOM
+
+// lanes of the reconciler
+const NoLane = 0
+const SyncLane = 1
+// priority of the scheduler
+const NormalPriority = 3
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMUpdatePriority', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should get the current update priority that is set', () => {
+    expect(ReactDOM.unstable_getCurrentUpdatePriority()).toBe(NoLane)
+    ReactDOM.unstable_setCurrentUpdatePriority(SyncLane)
+    expect(ReactDOM.unstable_getCurrentUpdatePriority()).toBe(SyncLane)
+    ReactDOM.unstable_setCurrentUpdatePriority(NoLane)
+    expect(ReactDOM.unstable_getCurrentUpdatePriority()).toBe(NoLane)
+  })
+
+  it('should use the current update priority as the lane of the update', async () => {
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    // a sync update is rendered in a micro task
+    ReactDOM.unstable_setCurrentUpdatePriority(SyncLane)
+    setCount(1)
+    ReactDOM.unstable_setCurrentUpdatePriority(NoLane)
+    await Promise.resolve()
+    expect(el.textContent).toBe('1')
+
+    // without a priority, the update is scheduled with the scheduler
+    setCount(2)
+    await Promise.resolve()
+    expect(el.textContent).toBe('1')
+    await sleep(10)
+    expect(el.textContent).toBe('2')
+  })
+
+  it('should render a sync update without the scheduler and a default update with it', async () => {
+    const log = []
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      log.push('render ' + count)
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    log.length = 0
+
+    // the sync update is flushed in a micro task, before the pending scheduler task
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => log.push('task'))
+    ReactDOM.unstable_setCurrentUpdatePriority(SyncLane)
+    setCount(1)
+    ReactDOM.unstable_setCurrentUpdatePriority(NoLane)
+    await Promise.resolve()
+    expect(log).toEqual(['render 1'])
+    await sleep(10)
+    expect(log).toEqual(['render 1', 'task'])
+
+    // the default update is a scheduler task, it runs after the task scheduled before it
+    log.length = 0
+    ReactDOM.unstable_scheduleCallback(NormalPriority, () => log.push('task'))
+    setCount(2)
+    await sleep(10)
+    expect(log).toEqual(['task', 'render 2'])
+    expect(el.textContent).toBe('2')
+  })
+})
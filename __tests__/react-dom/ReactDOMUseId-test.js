@@ -0,0 +1,72 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDOMUseId', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should give each component a unique id in the client id format', async () => {
+    function Child() {
+      const id = React.useId()
+      return <span id={id}>child</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <div>
+        <Child />
+        <Child />
+      </div>
+    )
+    await sleep(10)
+    const ids = Array.from(el.querySelectorAll('span')).map((span) => span.id)
+    expect(ids[0]).toMatch(/^:r[0-9a-v]+:$/)
+    expect(ids[1]).toMatch(/^:r[0-9a-v]+:$/)
+    expect(ids[0]).not.toBe(ids[1])
+  })
+
+  it('should keep the id when a conditional sibling toggles', async () => {
+    let id
+    function Child() {
+      id = React.useId()
+      return <span>child</span>
+    }
+
+    let setShow
+    function App() {
+      const [show, _setShow] = React.useState(false)
+      setShow = _setShow
+      return (
+        <div>
+          {show ? <b>sibling</b> : null}
+          <Child />
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    const initialId = id
+
+    setShow(true)
+    await sleep(10)
+    expect(el.textContent).toBe('siblingchild')
+    expect(id).toBe(initialId)
+
+    setShow(false)
+    await sleep(10)
+    expect(id).toBe(initialId)
+  })
+})
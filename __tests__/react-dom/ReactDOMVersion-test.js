@@ -0,0 +1,24 @@
+'use strict'
+
+let ReactDOM
+
+describe('ReactDOMVersion', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should return the implemented React version', () => {
+    expect(ReactDOM.version()).toBe('18.3.1')
+  })
+
+  it('should report the enabled features', () => {
+    const featureFlags = ReactDOM.getFeatureFlags()
+    expect(featureFlags.concurrentRoot).toBe(true)
+    expect(featureFlags.legacyRoot).toBe(true)
+    expect(featureFlags.transitions).toBe(true)
+    expect(featureFlags.suspense).toBe(true)
+    expect(featureFlags.portals).toBe(true)
+    expect(featureFlags.serverRendering).toBe(false)
+  })
+})
@@ -0,0 +1,90 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactDeferredValue', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should render the initial value first and then the real value', async () => {
+    const log = []
+    function App() {
+      const value = React.useDeferredValue('Final', 'Initial')
+      log.push(value)
+      return <span>{value}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(log).toEqual(['Initial', 'Final'])
+    expect(el.textContent).toBe('Final')
+  })
+
+  it('should render the value directly without an initial value', async () => {
+    const log = []
+    function App() {
+      const value = React.useDeferredValue('Final')
+      log.push(value)
+      return <span>{value}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(log).toEqual(['Final'])
+    expect(el.textContent).toBe('Final')
+  })
+
+  it('should keep the previous value during an urgent update', async () => {
+    const log = []
+    let setText
+    function App() {
+      const [text, _setText] = React.useState('A')
+      setText = _setText
+      const deferredText = React.useDeferredValue(text)
+      log.push(text + deferredText)
+      return <span>{deferredText}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(log).toEqual(['AA'])
+
+    log.length = 0
+    setText('B')
+    await sleep(10)
+    expect(log).toEqual(['BA', 'BB'])
+    expect(el.textContent).toBe('B')
+  })
+
+  it('should spawn one deferred render for both StrictMode passes', async () => {
+    const log = []
+    function App() {
+      const value = React.useDeferredValue('Final', 'Initial')
+      log.push(value)
+      return <span>{value}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <React.StrictMode>
+        <App />
+      </React.StrictMode>
+    )
+    await sleep(10)
+    expect(log).toEqual(['Initial', 'Initial', 'Final', 'Final'])
+    expect(el.textContent).toBe('Final')
+  })
+})
@@ -0,0 +1,249 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactHooks', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  afterEach(() => {
+    jest.restoreAllMocks()
+  })
+
+  function createApp(log) {
+    let setState
+    function App(props) {
+      const [count, _setCount] = React.useState(0)
+      setState = _setCount
+      React.useEffect(() => {
+        log.push('effect ' + count)
+      }, props.deps)
+      return <div>{count}</div>
+    }
+    return {App, setCount: (count) => setState(count)}
+  }
+
+  it('should fire the effect every render when deps change from an array to undefined', async () => {
+    const log = []
+    const {App, setCount} = createApp(log)
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<App deps={['a']} />)
+    await sleep(10)
+    expect(log).toEqual(['effect 0'])
+
+    root.render(<App deps={undefined} />)
+    await sleep(10)
+    expect(log).toEqual(['effect 0', 'effect 0'])
+
+    setCount(1)
+    await sleep(10)
+    expect(log).toEqual(['effect 0', 'effect 0', 'effect 1'])
+  })
+
+  it('should start gating the effect when deps change from undefined to an array', async () => {
+    const log = []
+    const {App, setCount} = createApp(log)
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<App deps={undefined} />)
+    await sleep(10)
+    expect(log).toEqual(['effect 0'])
+
+    root.render(<App deps={['a']} />)
+    await sleep(10)
+    expect(log).toEqual(['effect 0', 'effect 0'])
+
+    root.render(<App deps={['a']} />)
+    await sleep(10)
+    setCount(1)
+    await sleep(10)
+    expect(el.textContent).toBe('1')
+    expect(log).toEqual(['effect 0', 'effect 0'])
+  })
+
+  it('should warn when setting the state of a parent during the render of a child', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    let setParentState
+    let didUpdateParent = false
+    function Child() {
+      if (!didUpdateParent) {
+        didUpdateParent = true
+        setParentState(1)
+      }
+      return <span>child</span>
+    }
+
+    function Parent() {
+      const [state, setState] = React.useState(0)
+      setParentState = setState
+      return (
+        <div>
+          {state}
+          <Child />
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<Parent />)
+    await sleep(10)
+
+    expect(consoleError).toHaveBeenCalledWith(
+      expect.stringContaining(
+        'Cannot update a component (`Parent`) while rendering a different component (`Child`)'
+      )
+    )
+    expect(el.textContent).toBe('1child')
+  })
+
+  it('should not warn when the state is set outside of render', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    let setAppState
+    function App() {
+      const [state, setState] = React.useState(0)
+      setAppState = setState
+      return <div>{state}</div>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    setAppState(1)
+    await sleep(10)
+
+    expect(el.textContent).toBe('1')
+    expect(consoleError).not.toHaveBeenCalledWith(
+      expect.stringContaining('Cannot update a component')
+    )
+  })
+
+  it('should not re-render when the state is set to the same object', async () => {
+    const initialState = {count: 0}
+    let renderCount = 0
+    let state
+    let setState
+    function App() {
+      const [_state, _setState] = React.useState(initialState)
+      state = _state
+      setState = _setState
+      renderCount++
+      return <span>{_state.count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(renderCount).toBe(1)
+
+    setState(initialState)
+    setState((prevState) => prevState)
+    await sleep(10)
+    expect(renderCount).toBe(1)
+    expect(state).toBe(initialState)
+  })
+
+  it('should re-render when the state is set to an equal but different object', async () => {
+    const initialState = {count: 0}
+    let renderCount = 0
+    let state
+    let setState
+    function App() {
+      const [_state, _setState] = React.useState(initialState)
+      state = _state
+      setState = _setState
+      renderCount++
+      return <span>{_state.count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    const nextState = {count: 0}
+    setState(nextState)
+    await sleep(10)
+    expect(renderCount).toBe(2)
+    expect(state).toBe(nextState)
+  })
+
+  it('should apply the state updates dispatched while mounting before committing', async () => {
+    const commits = []
+    let renderCount = 0
+    function Counter() {
+      const [count, setCount] = React.useState(0)
+      renderCount++
+      if (count < 3) {
+        setCount((c) => c + 1)
+      }
+      React.useEffect(() => {
+        commits.push(count)
+      })
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<Counter />)
+    await sleep(10)
+    expect(el.textContent).toBe('3')
+    expect(renderCount).toBe(4)
+    expect(commits).toEqual([3])
+  })
+
+  it('should schedule the state updates dispatched by an effect of the mount commit', async () => {
+    const commits = []
+    function Counter() {
+      const [count, setCount] = React.useState(0)
+      React.useEffect(() => {
+        commits.push(count)
+        if (count === 0) {
+          setCount(1)
+        }
+      })
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<Counter />)
+    await sleep(10)
+    expect(el.textContent).toBe('1')
+    expect(commits).toEqual([0, 1])
+  })
+
+  it('should not commit a component that sets its state on every render', async () => {
+    let renders = 0
+    function App() {
+      const [count, setCount] = React.useState(0)
+      renders++
+      setCount(count + 1)
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    // the render is given up at the re-render limit instead of looping forever
+    expect(el.textContent).toBe('')
+    expect(renders).toBeLessThanOrEqual(25)
+
+    const rendersAfterError = renders
+    await sleep(10)
+    expect(renders).toBe(rendersAfterError)
+  })
+})
@@ -0,0 +1,65 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+function busyWait(ms) {
+  const start = performance.now()
+  while (performance.now() - start < ms) {}
+}
+
+describe('ReactInterleavedUpdates', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should apply an update scheduled during a yielding render in the follow-up render', async () => {
+    const log = []
+    let setCount
+    let scheduledInterleavedUpdate = false
+
+    function Slow(props) {
+      // spend longer than a frame so the concurrent render yields after each item
+      busyWait(6)
+      if (props.count === 1 && !scheduledInterleavedUpdate) {
+        scheduledInterleavedUpdate = true
+        setTimeout(() => setCount((count) => count + 1), 0)
+      }
+      return <span>{props.count}</span>
+    }
+
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      log.push('render ' + count)
+      return (
+        <div>
+          <Slow count={count} />
+          <Slow count={count} />
+          <Slow count={count} />
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('000')
+
+    // a default priority update is rendered concurrently
+    setTimeout(() => setCount(1), 0)
+    await sleep(200)
+
+    expect(scheduledInterleavedUpdate).toBe(true)
+    expect(log).toEqual(['render 0', 'render 1', 'render 2'])
+    expect(el.textContent).toBe('222')
+  })
+})
@@ -0,0 +1,155 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactMemo', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should bail out of a simple memo component on equal props', async () => {
+    const renders = []
+    const Child = React.memo(function Child(props) {
+      renders.push(props.label)
+      return <span>{props.label}</span>
+    })
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return (
+        <div>
+          {count}
+          <Child label={count > 1 ? 'updated' : 'initial'} />
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(renders).toEqual(['initial'])
+
+    setCount(1)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div>1<span>initial</span></div>')
+    expect(renders).toEqual(['initial'])
+
+    setCount(2)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div>2<span>updated</span></div>')
+    expect(renders).toEqual(['initial', 'updated'])
+  })
+
+  it('should run the hooks of a simple memo component in the memo fiber', async () => {
+    const log = []
+    let setValue
+    const Child = React.memo(function Child() {
+      const [value, _setValue] = React.useState(0)
+      setValue = _setValue
+      React.useEffect(() => {
+        log.push('effect ' + value)
+        return () => log.push('destroy ' + value)
+      }, [value])
+      return <span>{value}</span>
+    })
+
+    let setShow
+    function App() {
+      const [show, _setShow] = React.useState(true)
+      setShow = _setShow
+      return <div>{show ? <Child /> : null}</div>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(log).toEqual(['effect 0'])
+
+    setValue(1)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div><span>1</span></div>')
+    expect(log).toEqual(['effect 0', 'destroy 0', 'effect 1'])
+
+    setShow(false)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div></div>')
+    expect(log).toEqual(['effect 0', 'destroy 0', 'effect 1', 'destroy 1'])
+  })
+
+  it('should use the comparator of a memo component with a custom compare', async () => {
+    const renders = []
+    const Child = React.memo(
+      function Child(props) {
+        renders.push(props.value)
+        return <span>{props.value}</span>
+      },
+      (prevProps, nextProps) => prevProps.value % 2 === nextProps.value % 2
+    )
+
+    let setValue
+    function App() {
+      const [value, _setValue] = React.useState(0)
+      setValue = _setValue
+      return <Child value={value} />
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(renders).toEqual([0])
+
+    setValue(2)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<span>0</span>')
+    expect(renders).toEqual([0])
+
+    setValue(3)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<span>3</span>')
+    expect(renders).toEqual([0, 3])
+  })
+
+  it('should bail out of the same element without comparing its props', async () => {
+    const renders = []
+    const compare = jest.fn(() => true)
+    const Child = React.memo(function Child(props) {
+      renders.push(props.label)
+      return <span>{props.label}</span>
+    }, compare)
+    const child = <Child label='child' />
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return (
+        <div>
+          {count}
+          {child}
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(renders).toEqual(['child'])
+
+    setCount(1)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div>1<span>child</span></div>')
+    expect(renders).toEqual(['child'])
+    expect(compare).not.toHaveBeenCalled()
+  })
+})
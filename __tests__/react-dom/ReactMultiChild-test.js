@@ -0,0 +1,219 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactMultiChild', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should reuse the single child by key when it becomes an array', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        <div key='a'>a</div>
+      </section>
+    )
+    await sleep(10)
+    const div = el.querySelector('div')
+
+    root.render(
+      <section>
+        {[<div key='a'>a</div>, <span key='b'>b</span>]}
+      </section>
+    )
+    await sleep(10)
+    expect(el.querySelector('section').innerHTML).toBe(
+      '<div>a</div><span>b</span>'
+    )
+    expect(el.querySelector('div')).toBe(div)
+  })
+
+  it('should reuse the matched child by key when an array becomes a single child', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        {[<span key='b'>b</span>, <div key='a'>a</div>]}
+      </section>
+    )
+    await sleep(10)
+    const div = el.querySelector('div')
+
+    root.render(
+      <section>
+        <div key='a'>a</div>
+      </section>
+    )
+    await sleep(10)
+    expect(el.querySelector('section').innerHTML).toBe('<div>a</div>')
+    expect(el.querySelector('div')).toBe(div)
+  })
+
+  it('should replace the child when the key matches but the type changes', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        <div key='a'>a</div>
+      </section>
+    )
+    await sleep(10)
+
+    root.render(
+      <section>
+        {[<p key='a'>a</p>, <span key='b'>b</span>]}
+      </section>
+    )
+    await sleep(10)
+    expect(el.querySelector('section').innerHTML).toBe('<p>a</p><span>b</span>')
+  })
+
+  it('should reuse the children of a single fragment', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        <React.Fragment>
+          <div>a</div>
+        </React.Fragment>
+      </section>
+    )
+    await sleep(10)
+    const div = el.querySelector('div')
+
+    root.render(
+      <section>
+        <React.Fragment>
+          <div>b</div>
+        </React.Fragment>
+      </section>
+    )
+    await sleep(10)
+    expect(el.querySelector('section').innerHTML).toBe('<div>b</div>')
+    expect(el.querySelector('div')).toBe(div)
+  })
+
+  it('should collapse an array into an empty list', async () => {
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(
+      <section>
+        {[<div key='a'>a</div>, <span key='b'>b</span>]}
+      </section>
+    )
+    await sleep(10)
+
+    root.render(<section>{[]}</section>)
+    await sleep(10)
+    expect(el.querySelector('section').innerHTML).toBe('')
+  })
+
+  it('should warn once when two children have the same key', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <ul>
+        {[<li key='a'>1</li>, <li key='a'>2</li>, <li key='b'>3</li>]}
+      </ul>
+    )
+    await sleep(10)
+
+    const duplicateKeyErrors = consoleError.mock.calls.filter(([message]) =>
+      String(message).includes('Encountered two children with the same key')
+    )
+    expect(duplicateKeyErrors.length).toBe(1)
+    expect(duplicateKeyErrors[0][0]).toContain('`a`')
+    // the children are still rendered
+    expect(el.textContent).toBe('123')
+    consoleError.mockRestore()
+  })
+
+  it('should mount the children of an iterable in order', async () => {
+    const items = new Map([
+      ['a', <li key='a'>1</li>],
+      ['b', <li key='b'>2</li>],
+      ['c', <li key='c'>3</li>],
+    ])
+
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<ul>{items.values()}</ul>)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<ul><li>1</li><li>2</li><li>3</li></ul>')
+
+    items.delete('a')
+    root.render(<ul>{items.values()}</ul>)
+    await sleep(10)
+    expect(el.innerHTML).toBe('<ul><li>2</li><li>3</li></ul>')
+  })
+
+  it('should warn when the children of an iterable have no key', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    function* items() {
+      yield <li>1</li>
+      yield <li>2</li>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<ul>{items()}</ul>)
+    await sleep(10)
+
+    const missingKeyErrors = consoleError.mock.calls.filter(([message]) =>
+      String(message).includes('Each child in a list should have a unique "key" prop')
+    )
+    expect(missingKeyErrors.length).toBe(1)
+    expect(el.textContent).toBe('12')
+    consoleError.mockRestore()
+  })
+
+  it('should move the host nodes of reordered children instead of recreating them', async () => {
+    function List(props) {
+      return (
+        <div>
+          {props.keys.map((key) => (
+            <input key={key} defaultValue={key} />
+          ))}
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    const root = ReactDOM.createRoot(el)
+    root.render(<List keys={['a', 'b', 'c']} />)
+    await sleep(10)
+
+    const [a, b, c] = el.querySelectorAll('input')
+    // the values the user typed are only kept by the nodes themselves
+    a.value = 'typed a'
+    c.value = 'typed c'
+
+    root.render(<List keys={['c', 'a', 'b']} />)
+    await sleep(10)
+    const inputs = Array.from(el.querySelectorAll('input'))
+    expect(inputs[0]).toBe(c)
+    expect(inputs[1]).toBe(a)
+    expect(inputs[2]).toBe(b)
+    expect(inputs.map((input) => input.value)).toEqual([
+      'typed c',
+      'typed a',
+      'b'
+    ])
+  })
+})
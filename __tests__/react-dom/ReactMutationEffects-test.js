@@ -0,0 +1,90 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactMutationEffects', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should commit a placement and a deletion in different subtrees', async () => {
+    const log = []
+    function Child(props) {
+      React.useEffect(() => {
+        log.push('mount ' + props.name)
+        return () => log.push('unmount ' + props.name)
+      }, [])
+      return <i>{props.name}</i>
+    }
+
+    let setStep
+    function App() {
+      const [step, _setStep] = React.useState(0)
+      setStep = _setStep
+      return (
+        <div>
+          <section>
+            {step === 1 ? <p>placed</p> : null}
+            <span>stay</span>
+          </section>
+          <article>
+            {step === 0 ? (
+              <b>
+                <Child name='deleted' />
+              </b>
+            ) : null}
+            <em>stay</em>
+          </article>
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.innerHTML).toBe(
+      '<div><section><span>stay</span></section>' +
+        '<article><b><i>deleted</i></b><em>stay</em></article></div>'
+    )
+
+    setStep(1)
+    await sleep(10)
+    expect(el.innerHTML).toBe(
+      '<div><section><p>placed</p><span>stay</span></section>' +
+        '<article><em>stay</em></article></div>'
+    )
+    expect(log).toEqual(['mount deleted', 'unmount deleted'])
+  })
+
+  it('should insert before a sibling when the previous sibling is deleted in the same commit', async () => {
+    let setItems
+    function App() {
+      const [items, _setItems] = React.useState(['a', 'b'])
+      setItems = _setItems
+      return (
+        <ul>
+          {items.map((item) => (
+            <li key={item}>{item}</li>
+          ))}
+        </ul>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    setItems(['c', 'b'])
+    await sleep(10)
+    expect(el.innerHTML).toBe('<ul><li>c</li><li>b</li></ul>')
+  })
+})
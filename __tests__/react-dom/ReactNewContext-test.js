@@ -0,0 +1,171 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactNewContext', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should pop the provider so that siblings read the outer value', async () => {
+    const Context = React.createContext('default')
+
+    function Reader() {
+      const value = React.useContext(Context)
+      return <span>{value}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <div>
+        <Context.Provider value='outer'>
+          <Context.Provider value='inner'>
+            <Reader />
+          </Context.Provider>
+          <Reader />
+          <Context.Consumer>{(value) => <span>{value}</span>}</Context.Consumer>
+        </Context.Provider>
+        <Reader />
+        <Context.Consumer>{(value) => <span>{value}</span>}</Context.Consumer>
+      </div>
+    )
+    await sleep(10)
+    expect(
+      Array.from(el.querySelectorAll('span')).map((span) => span.textContent)
+    ).toEqual(['inner', 'outer', 'outer', 'default', 'default'])
+  })
+
+  it('should update the consumer when the provider value changes', async () => {
+    const Context = React.createContext(0)
+
+    let setValue
+    function App() {
+      const [value, _setValue] = React.useState(1)
+      setValue = _setValue
+      return (
+        <Context.Provider value={value}>
+          <Static />
+        </Context.Provider>
+      )
+    }
+
+    const Static = React.memo(function Static() {
+      return (
+        <Context.Consumer>{(value) => <span>{value}</span>}</Context.Consumer>
+      )
+    })
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('1')
+
+    setValue(2)
+    await sleep(10)
+    expect(el.textContent).toBe('2')
+  })
+
+  it('should read the context with use inside a condition', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+    const Context = React.createContext('default')
+
+    function Reader(props) {
+      const [count] = React.useState(0)
+      let value = 'skipped'
+      if (props.read) {
+        value = React.use(Context)
+      }
+      const [label] = React.useState('label')
+      return <span>{[label, value, count].join(' ')}</span>
+    }
+
+    let setRead
+    function App() {
+      const [read, _setRead] = React.useState(false)
+      setRead = _setRead
+      return (
+        <Context.Provider value='provided'>
+          <Reader read={read} />
+        </Context.Provider>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('label skipped 0')
+
+    setRead(true)
+    await sleep(10)
+    expect(el.textContent).toBe('label provided 0')
+
+    setRead(false)
+    await sleep(10)
+    expect(el.textContent).toBe('label skipped 0')
+    expect(consoleError).not.toHaveBeenCalled()
+    consoleError.mockRestore()
+  })
+
+  it('should suspend on a pending thenable passed to use', async () => {
+    let resolve
+    const promise = new Promise((r) => {
+      resolve = r
+    })
+
+    function Text() {
+      return <span>{React.use(promise)}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <React.Suspense fallback='Loading'>
+        <Text />
+      </React.Suspense>
+    )
+    await sleep(10)
+    expect(el.textContent).toBe('Loading')
+
+    resolve('done')
+    await sleep(10)
+    expect(el.textContent).toBe('done')
+  })
+
+  it('should unwind the providers of a render that threw', async () => {
+    const Context = React.createContext('default')
+
+    function Thrower() {
+      throw new Error('Oops')
+    }
+
+    function Reader() {
+      const value = React.useContext(Context)
+      return <span>{value}</span>
+    }
+
+    const errorEl = document.createElement('div')
+    ReactDOM.createRoot(errorEl).render(
+      <Context.Provider value='outer'>
+        <Context.Provider value='inner'>
+          <Thrower />
+        </Context.Provider>
+      </Context.Provider>
+    )
+    await sleep(10)
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<Reader />)
+    await sleep(10)
+    expect(el.textContent).toBe('default')
+  })
+})
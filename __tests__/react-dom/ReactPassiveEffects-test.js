@@ -0,0 +1,133 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactPassiveEffects', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  function createApp(log) {
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      log.push('render ' + count)
+      React.useEffect(() => {
+        log.push('effect ' + count)
+      }, [count])
+      return <span>{count}</span>
+    }
+    return {App, setCount: (count) => setCount(count)}
+  }
+
+  it('should run the passive effects asynchronously after the commit', async () => {
+    const log = []
+    const {App} = createApp(log)
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+
+    // committed in a micro task, the effects are scheduled in a later task
+    await Promise.resolve()
+    expect(el.textContent).toBe('0')
+    expect(log).toEqual(['render 0'])
+
+    await sleep(10)
+    expect(log).toEqual(['render 0', 'effect 0'])
+  })
+
+  it('should flush the pending passive effects before the next sync render', async () => {
+    const log = []
+    const {App, setCount} = createApp(log)
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await Promise.resolve()
+    expect(log).toEqual(['render 0'])
+
+    ReactDOM.flushSync(() => setCount(1))
+    expect(log).toEqual(['render 0', 'effect 0', 'render 1'])
+    expect(el.textContent).toBe('1')
+
+    await sleep(10)
+    expect(log).toEqual(['render 0', 'effect 0', 'render 1', 'effect 1'])
+  })
+
+  it('should warn when an effect returns a Promise', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    function App() {
+      React.useEffect(async () => {
+        await Promise.resolve()
+      }, [])
+      return <span>async</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    const effectErrors = consoleError.mock.calls.filter(([message]) =>
+      String(message).includes('useEffect must not return anything besides a function')
+    )
+    expect(effectErrors.length).toBe(1)
+    expect(effectErrors[0][0]).toContain('useEffect(async () => ...)')
+    consoleError.mockRestore()
+  })
+
+  it('should not warn when an effect returns a cleanup function or nothing', async () => {
+    const consoleError = jest
+      .spyOn(console, 'error')
+      .mockImplementation(() => {})
+
+    function App() {
+      React.useEffect(() => {}, [])
+      React.useEffect(() => () => {}, [])
+      return <span>sync</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    const effectErrors = consoleError.mock.calls.filter(([message]) =>
+      String(message).includes('useEffect must not return anything besides a function')
+    )
+    expect(effectErrors.length).toBe(0)
+    consoleError.mockRestore()
+  })
+
+  it('should flush the deferred effects of every root that was committed', async () => {
+    const log = []
+    function Child({name}) {
+      React.useEffect(() => {
+        log.push('effect ' + name)
+      }, [])
+      return <span>{name}</span>
+    }
+
+    const elA = document.createElement('div')
+    const elB = document.createElement('div')
+    ReactDOM.createRoot(elA).render(<Child name='A' />)
+    ReactDOM.createRoot(elB).render(<Child name='B' />)
+
+    // both roots are committed before the deferred flush runs
+    await Promise.resolve()
+    expect(elA.textContent).toBe('A')
+    expect(elB.textContent).toBe('B')
+    expect(log).toEqual(['effect A'])
+
+    await sleep(10)
+    expect(log).toEqual(['effect A', 'effect B'])
+  })
+})
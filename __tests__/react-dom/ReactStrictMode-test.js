@@ -0,0 +1,68 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactStrictMode', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should render the components under StrictMode twice', async () => {
+    const log = []
+    function Child({name}) {
+      log.push(name)
+      return <span>{name}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <div>
+        <React.StrictMode>
+          <Child name='inside' />
+        </React.StrictMode>
+        <Child name='outside' />
+      </div>
+    )
+    await sleep(10)
+    expect(el.innerHTML).toBe('<div><span>inside</span><span>outside</span></div>')
+    expect(log.filter((name) => name === 'inside').length).toBe(2)
+    expect(log.filter((name) => name === 'outside').length).toBe(1)
+  })
+
+  it('should keep the state of the first render pass and run the initializer twice', async () => {
+    let setCount
+    let initCount = 0
+    function Counter() {
+      const [count, _setCount] = React.useState(() => {
+        initCount++
+        return 0
+      })
+      setCount = _setCount
+      return <span>{count}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <React.StrictMode>
+        <Counter />
+      </React.StrictMode>
+    )
+    await sleep(10)
+    expect(el.textContent).toBe('0')
+    // the initializer is called twice, so an impure one shows up
+    expect(initCount).toBe(2)
+
+    setCount((count) => count + 1)
+    await sleep(10)
+    expect(el.textContent).toBe('1')
+  })
+})
@@ -0,0 +1,218 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+function createResource() {
+  const cache = new Map()
+  const pending = new Map()
+  return {
+    read(key) {
+      if (cache.has(key)) {
+        return cache.get(key)
+      }
+      if (!pending.has(key)) {
+        let resolve
+        const promise = new Promise((r) => {
+          resolve = r
+        })
+        pending.set(key, {promise, resolve})
+      }
+      throw pending.get(key).promise
+    },
+    resolve(key, value) {
+      cache.set(key, value)
+      if (pending.has(key)) {
+        pending.get(key).resolve(value)
+        pending.delete(key)
+      }
+    }
+  }
+}
+
+describe('ReactSuspense', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should show the fallback until the data resolves', async () => {
+    const resource = createResource()
+
+    function Text() {
+      return <span>{resource.read('A')}</span>
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(
+      <React.Suspense fallback='Loading'>
+        <Text />
+      </React.Suspense>
+    )
+    await sleep(10)
+    expect(el.textContent).toBe('Loading')
+
+    resource.resolve('A', 'A')
+    await sleep(10)
+    expect(el.textContent).toBe('A')
+  })
+
+  it('should reuse the offscreen children when toggling between content and fallback', async () => {
+    const resource = createResource()
+    resource.resolve('A', 'A')
+
+    let mountCount = 0
+    function Stateful() {
+      const [id] = React.useState(() => ++mountCount)
+      return <span>{'id:' + id}</span>
+    }
+
+    function Text(props) {
+      return <b>{resource.read(props.text)}</b>
+    }
+
+    let setText
+    function App() {
+      const [text, _setText] = React.useState('A')
+      setText = _setText
+      return (
+        <React.Suspense fallback={<i>Loading</i>}>
+          <Stateful />
+          <Text text={text} />
+        </React.Suspense>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('id:1A')
+    const span = el.querySelector('span')
+
+    // suspend, the primary children are hidden rather than unmounted
+    setText('B')
+    await sleep(10)
+    expect(el.querySelector('span')).toBe(span)
+    expect(span.style.display).toBe('none')
+    expect(el.querySelector('i').textContent).toBe('Loading')
+
+    // resolve, the same primary children are shown again
+    resource.resolve('B', 'B')
+    await sleep(10)
+    expect(el.querySelector('span')).toBe(span)
+    expect(span.style.display).toBe('')
+    expect(el.querySelector('i')).toBe(null)
+    expect(el.textContent).toBe('id:1B')
+    expect(mountCount).toBe(1)
+
+    // suspend again with the reused offscreen fiber
+    setText('C')
+    await sleep(10)
+    expect(el.querySelector('span')).toBe(span)
+    expect(span.style.display).toBe('none')
+
+    resource.resolve('C', 'C')
+    await sleep(10)
+    expect(el.textContent).toBe('id:1C')
+    expect(mountCount).toBe(1)
+  })
+
+  it('should not suspend again when re-rendering a resolved key', async () => {
+    const resource = createResource()
+
+    let suspendCount = 0
+    function Text(props) {
+      try {
+        return <span>{resource.read(props.text)}</span>
+      } catch (thenable) {
+        suspendCount++
+        throw thenable
+      }
+    }
+
+    let setCount
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      setCount = _setCount
+      return (
+        <React.Suspense fallback={<i>Loading</i>}>
+          <Text text='A' />
+          <b>{count}</b>
+        </React.Suspense>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('Loading')
+    expect(suspendCount).toBe(1)
+
+    resource.resolve('A', 'A')
+    await sleep(10)
+    expect(el.textContent).toBe('A0')
+
+    setCount(1)
+    await sleep(10)
+    expect(el.querySelector('i')).toBe(null)
+    expect(el.textContent).toBe('A1')
+    expect(suspendCount).toBe(1)
+  })
+
+  it('should reveal the boundaries resolved close together in a single commit', async () => {
+    const resource = createResource()
+    const commits = []
+
+    function Text(props) {
+      const text = resource.read(props.text)
+      React.useEffect(() => {
+        commits.push(el.textContent)
+      }, [])
+      return <span>{text}</span>
+    }
+
+    let setShow
+    function App() {
+      const [show, _setShow] = React.useState(false)
+      setShow = _setShow
+      if (!show) {
+        return null
+      }
+      return (
+        <div>
+          <React.Suspense fallback='Loading A'>
+            <Text text='A' />
+          </React.Suspense>
+          <React.Suspense fallback='Loading B'>
+            <Text text='B' />
+          </React.Suspense>
+        </div>
+      )
+    }
+
+    const el = document.createElement('div')
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+
+    React.startTransition(() => setShow(true))
+    await sleep(10)
+    expect(el.textContent).toBe('Loading ALoading B')
+
+    resource.resolve('A', 'A')
+    await sleep(50)
+    // the retry is held back in case another boundary resolves soon
+    expect(el.textContent).toBe('Loading ALoading B')
+
+    resource.resolve('B', 'B')
+    await sleep(400)
+    expect(el.textContent).toBe('AB')
+    expect(commits).toEqual(['AB', 'AB'])
+  })
+})
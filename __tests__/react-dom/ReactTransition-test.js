@@ -0,0 +1,176 @@
+'use strict'
+
+let React
+let ReactDOM
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactTransition', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactDOM = require('../../dist/react-dom')
+  })
+
+  it('should commit the updates of the same transition together', async () => {
+    const el = document.createElement('div')
+    const commits = []
+
+    let setA
+    function A() {
+      const [a, _setA] = React.useState('A0')
+      setA = _setA
+      React.useEffect(() => {
+        commits.push(el.textContent)
+      }, [a])
+      return <span>{a}</span>
+    }
+
+    let setB
+    function B() {
+      const [b, _setB] = React.useState('B0')
+      setB = _setB
+      React.useEffect(() => {
+        commits.push(el.textContent)
+      }, [b])
+      return <span>{b}</span>
+    }
+
+    ReactDOM.createRoot(el).render(
+      <div>
+        <A />
+        <B />
+      </div>
+    )
+    await sleep(10)
+    expect(el.textContent).toBe('A0B0')
+
+    commits.length = 0
+    React.startTransition(() => {
+      setA('A1')
+      setB('B1')
+    })
+    await sleep(10)
+    // there is no commit in which only one of the updates is applied
+    expect(commits).toEqual(['A1B1', 'A1B1'])
+  })
+
+  it('should not entangle the updates of different transitions', async () => {
+    const el = document.createElement('div')
+    const renders = []
+
+    let setA
+    function A() {
+      const [a, _setA] = React.useState('A0')
+      setA = _setA
+      renders.push(a)
+      return <span>{a}</span>
+    }
+
+    let setB
+    function B() {
+      const [b, _setB] = React.useState('B0')
+      setB = _setB
+      renders.push(b)
+      return <span>{b}</span>
+    }
+
+    ReactDOM.createRoot(el).render(
+      <div>
+        <A />
+        <B />
+      </div>
+    )
+    await sleep(10)
+
+    renders.length = 0
+    React.startTransition(() => setA('A1'))
+    React.startTransition(() => setB('B1'))
+    await sleep(10)
+    expect(renders).toEqual(['A1', 'B1'])
+    expect(el.textContent).toBe('A1B1')
+  })
+
+  it('should keep isPending true until an async action settles', async () => {
+    const el = document.createElement('div')
+
+    let resolve
+    const promise = new Promise((_resolve) => {
+      resolve = _resolve
+    })
+
+    let startTransition
+    let setValue
+    function App() {
+      const [isPending, _startTransition] = React.useTransition()
+      const [value, _setValue] = React.useState('A')
+      startTransition = _startTransition
+      setValue = _setValue
+      return <span>{isPending ? 'Pending ' + value : value}</span>
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('A')
+
+    startTransition(async () => {
+      await promise
+      startTransition(() => setValue('B'))
+    })
+    await sleep(10)
+    expect(el.textContent).toBe('Pending A')
+
+    resolve()
+    await sleep(10)
+    expect(el.textContent).toBe('B')
+  })
+
+  it('should render a starved transition synchronously once it expires', async () => {
+    const el = document.createElement('div')
+
+    let setCount
+    let setValue
+    function App() {
+      const [count, _setCount] = React.useState(0)
+      const [value, _setValue] = React.useState('A')
+      setCount = _setCount
+      setValue = _setValue
+      return (
+        <span>
+          {count} {value}
+        </span>
+      )
+    }
+
+    ReactDOM.createRoot(el).render(<App />)
+    await sleep(10)
+    expect(el.textContent).toBe('0 A')
+
+    // the scheduler reads performance.now and falls back to Date.now
+    let now = performance.now()
+    const performanceNow = jest
+      .spyOn(performance, 'now')
+      .mockImplementation(() => now)
+    const dateNow = jest.spyOn(Date, 'now').mockImplementation(() => now)
+
+    React.startTransition(() => setValue('B'))
+    // the transition is preempted by a sync update before it gets to render
+    for (let count = 1; count < 5; count++) {
+      now += 1000
+      ReactDOM.flushSync(() => setCount(count))
+      expect(el.textContent).toBe(count + ' A')
+    }
+
+    // past 5000ms the transition is expired and rendered with the sync work
+    now += 1000
+    ReactDOM.flushSync(() => setCount(5))
+    expect(el.textContent).toBe('5 B')
+
+    performanceNow.mockRestore()
+    dateNow.mockRestore()
+  })
+})
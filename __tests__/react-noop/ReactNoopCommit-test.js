@@ -0,0 +1,106 @@
+'use strict'
+
+let React
+let ReactNoop
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactNoopCommit', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactNoop = require('../../dist/react-noop')
+  })
+
+  it('should apply the text updates of a commit between prepare and reset', async () => {
+    const items = Array.from({length: 100}, (_, i) => i)
+    function List(props) {
+      return (
+        <div>
+          {items.map((i) => (
+            <span key={i}>{props.label + i}</span>
+          ))}
+        </div>
+      )
+    }
+
+    const root = ReactNoop.createRoot()
+    root.render(<List label='a' />)
+    await sleep(10)
+
+    const before = ReactNoop.getCommitStats()
+    root.render(<List label='b' />)
+    await sleep(10)
+    const after = ReactNoop.getCommitStats()
+
+    expect(after.commits - before.commits).toBe(1)
+    expect(after.textUpdates - before.textUpdates).toBe(100)
+    expect(after.textUpdatesOutsideCommit).toBe(0)
+  })
+
+  it('should detach every host instance of a deleted subtree', async () => {
+    function App(props) {
+      return props.show ? (
+        <div>
+          <span>a</span>
+          <span>b</span>
+          text
+        </div>
+      ) : null
+    }
+
+    const root = ReactNoop.createRoot()
+    root.render(<App show={true} />)
+    await sleep(10)
+    expect(ReactNoop.takeDetachedInstances()).toEqual([])
+
+    root.render(<App show={false} />)
+    await sleep(10)
+    const detached = ReactNoop.takeDetachedInstances()
+    expect(detached.map((instance) => instance.type || instance.text)).toEqual([
+      'div',
+      'span',
+      'a',
+      'span',
+      'b',
+      'text'
+    ])
+  })
+
+  it('should move the instances of reordered children', async () => {
+    const refs = {}
+    function List(props) {
+      return (
+        <div>
+          {props.keys.map((key) => (
+            <span key={key} ref={(instance) => (refs[key] = instance)}>
+              {key}
+            </span>
+          ))}
+        </div>
+      )
+    }
+
+    const root = ReactNoop.createRoot()
+    root.render(<List keys={['a', 'b', 'c']} />)
+    await sleep(10)
+    const {a, b, c} = refs
+
+    root.render(<List keys={['c', 'a', 'b']} />)
+    await sleep(10)
+    const div = root.getChildrenAsJSX()
+    expect(div.props.children.map((span) => span.props.children)).toEqual([
+      'c',
+      'a',
+      'b'
+    ])
+    expect(refs.a).toBe(a)
+    expect(refs.b).toBe(b)
+    expect(refs.c).toBe(c)
+    expect(ReactNoop.takeDetachedInstances()).toEqual([])
+  })
+})
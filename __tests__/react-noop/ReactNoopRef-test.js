@@ -0,0 +1,37 @@
+'use strict'
+
+let React
+let ReactNoop
+
+function sleep(ms) {
+  return new Promise((resolve) => {
+    setTimeout(resolve, ms)
+  })
+}
+
+describe('ReactNoopRef', () => {
+  beforeEach(() => {
+    jest.resetModules()
+    React = require('../../dist/react')
+    ReactNoop = require('../../dist/react-noop')
+  })
+
+  it('should attach the public instance of the host config to a ref', async () => {
+    const ref = {current: null}
+    let callbackInstance = null
+
+    const root = ReactNoop.createRoot()
+    root.render(
+      <div>
+        <span ref={ref} />
+        <p ref={(instance) => (callbackInstance = instance)} />
+      </div>
+    )
+    await sleep(10)
+
+    // the noop renderer exposes its own instance records instead of DOM nodes
+    expect(ref.current instanceof Node).toBe(false)
+    expect(ref.current.type).toBe('span')
+    expect(callbackInstance.type).toBe('p')
+  })
+})
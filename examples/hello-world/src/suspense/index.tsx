@@ -0,0 +1,54 @@
+import {Suspense, useState} from 'react'
+
+type Record =
+  | {status: 'pending'; value: Promise<void>}
+  | {status: 'fulfilled'; value: string}
+  | {status: 'rejected'; value: unknown}
+
+// Once a key is resolved it is read synchronously, the component doesn't suspend again
+const cache = new Map<number, Record>()
+
+function fetchData(id: number): string {
+  let record = cache.get(id)
+  if (record === undefined) {
+    const promise = new Promise<string>((resolve) => {
+      setTimeout(() => resolve(`data ${id}`), 1000)
+    }).then(
+      (value) => {
+        cache.set(id, {status: 'fulfilled', value})
+      },
+      (error) => {
+        cache.set(id, {status: 'rejected', value: error})
+      }
+    )
+    record = {status: 'pending', value: promise}
+    cache.set(id, record)
+  }
+  if (record.status === 'fulfilled') {
+    return record.value
+  }
+  throw record.value
+}
+
+export default function App() {
+  const [id, setId] = useState(0)
+  return (
+    <div>
+      <button onClick={() => setId(id + 1)}>next: {id}</button>
+      <Suspense fallback={<div>loading...</div>}>
+        <Counter />
+        <Data id={id} />
+      </Suspense>
+    </div>
+  )
+}
+
+// The state of Counter is preserved while the fallback is shown
+function Counter() {
+  const [num, setNum] = useState(0)
+  return <p onClick={() => setNum(num + 1)}>counter: {num}</p>
+}
+
+function Data({id}: {id: number}) {
+  return <p>{fetchData(id)}</p>
+}
@@ -0,0 +1,134 @@
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+use web_sys::Element;
+
+use shared::{derive_from_js_value, type_of};
+
+use crate::host_config::to_string;
+
+fn is_event_prop(key: &str) -> bool {
+    key.len() > 2
+        && key.starts_with("on")
+        && key.chars().nth(2).map_or(false, |c| c.is_ascii_uppercase())
+}
+
+// Props that are handled by the reconciler or the event system instead of being set on the element
+fn is_reserved_prop(key: &str) -> bool {
+    match key {
+        "children" | "autoFocus" | "style" | "dangerouslySetInnerHTML" => true,
+        _ => is_event_prop(key),
+    }
+}
+
+fn is_empty(value: &JsValue) -> bool {
+    value.is_null() || value.is_undefined()
+}
+
+fn prop_keys(props: &JsValue) -> Vec<String> {
+    if !props.is_object() {
+        return vec![];
+    }
+    Object::keys(&Object::from(props.clone()))
+        .iter()
+        .filter_map(|key| key.as_string())
+        .filter(|key| !is_reserved_prop(key))
+        .collect()
+}
+
+fn set_attribute(element: &Element, key: &str, value: &JsValue) {
+    let name = match key {
+        "className" => "class",
+        "htmlFor" => "for",
+        _ => key,
+    };
+    if is_empty(value) || value.as_bool() == Some(false) {
+        let _ = element.remove_attribute(name);
+    } else if value.as_bool() == Some(true) {
+        let _ = element.set_attribute(name, "");
+    } else if !type_of(value, "function") && !type_of(value, "symbol") {
+        let _ = element.set_attribute(name, to_string(value).as_str());
+    }
+}
+
+fn set_property(element: &Element, key: &str, value: &JsValue) {
+    let _ = Reflect::set(element, &key.into(), value);
+}
+
+// A controlled value is written to the property, and only when it differs, so that the cursor of
+// an input that is being edited isn't moved
+fn set_controlled_value(element: &Element, value: &JsValue) {
+    let value = JsValue::from_str(to_string(value).as_str());
+    if derive_from_js_value(element, "value") != value {
+        set_property(element, "value", &value);
+    }
+}
+
+fn set_controlled_checked(element: &Element, checked: &JsValue) {
+    let checked = JsValue::from_bool(checked.is_truthy());
+    if derive_from_js_value(element, "checked") != checked {
+        set_property(element, "checked", &checked);
+    }
+}
+
+// Called once the children of the element are appended, a select needs its options for its value
+pub fn set_initial_properties(element: &Element, props: &JsValue) {
+    for key in prop_keys(props) {
+        let value = derive_from_js_value(props, key.as_str());
+        match key.as_str() {
+            // Set after the defaults, a controlled value wins over defaultValue
+            "value" | "checked" => {}
+            "defaultValue" | "defaultChecked" | "selected" => {
+                if !is_empty(&value) {
+                    set_property(element, key.as_str(), &value);
+                }
+            }
+            _ => set_attribute(element, key.as_str(), &value),
+        }
+    }
+
+    let value = derive_from_js_value(props, "value");
+    if !is_empty(&value) {
+        set_controlled_value(element, &value);
+    }
+    let checked = derive_from_js_value(props, "checked");
+    if !is_empty(&checked) {
+        set_controlled_checked(element, &checked);
+    }
+}
+
+pub fn update_properties(element: &Element, last_props: &JsValue, next_props: &JsValue) {
+    for key in prop_keys(last_props) {
+        if derive_from_js_value(next_props, key.as_str()).is_undefined() {
+            match key.as_str() {
+                // An element that stops being controlled keeps its current value
+                "value" | "checked" | "defaultValue" | "defaultChecked" => {}
+                "selected" => set_property(element, "selected", &JsValue::FALSE),
+                _ => set_attribute(element, key.as_str(), &JsValue::null()),
+            }
+        }
+    }
+
+    for key in prop_keys(next_props) {
+        let value = derive_from_js_value(next_props, key.as_str());
+        match key.as_str() {
+            // The defaults only apply to the mount, after that the element owns its value
+            "value" | "checked" | "defaultValue" | "defaultChecked" => {}
+            "selected" => set_property(element, "selected", &JsValue::from_bool(value.is_truthy())),
+            _ => {
+                if derive_from_js_value(last_props, key.as_str()) != value {
+                    set_attribute(element, key.as_str(), &value);
+                }
+            }
+        }
+    }
+
+    // Controlled props are restored even when they didn't change, the user may have edited them
+    let value = derive_from_js_value(next_props, "value");
+    if !is_empty(&value) {
+        set_controlled_value(element, &value);
+    }
+    let checked = derive_from_js_value(next_props, "checked");
+    if !is_empty(&checked) {
+        set_controlled_checked(element, &checked);
+    }
+}
@@ -3,17 +3,18 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use js_sys::JSON::stringify;
-use js_sys::{global, Function, Promise};
+use js_sys::{global, Function, Object, Promise};
 use react_reconciler::work_tags::WorkTag;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
-use web_sys::{window, Element, Node};
+use web_sys::{window, Document, Element, HtmlElement, Node};
 
 use react_reconciler::fiber::FiberNode;
 use react_reconciler::HostConfig;
 use shared::{derive_from_js_value, log, type_of};
 
-use crate::synthetic_event::update_fiber_props;
+use crate::dom_component::{set_initial_properties, update_properties};
+use crate::synthetic_event::{detach_fiber_props, update_fiber_props};
 
 pub struct ReactDomHostConfig;
 
@@ -50,11 +51,102 @@ extern "C" {
     fn hasQueueMicrotask(this: &Global) -> JsValue;
 }
 
+// The value of an attribute selector, as a CSS string
+fn escape_selector_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// The options of a resource hint are optional
+fn normalize_options(options: &JsValue) -> JsValue {
+    if options.is_object() {
+        options.clone()
+    } else {
+        Object::new().into()
+    }
+}
+
+fn set_string_attribute(element: &Element, name: &str, options: &JsValue, key: &str) {
+    if let Some(value) = derive_from_js_value(options, key).as_string() {
+        let _ = element.set_attribute(name, value.as_str());
+    }
+}
+
 impl ReactDomHostConfig {
     fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: &JsValue) {
         let text_instance = text_instance.clone().downcast::<Node>().unwrap();
         text_instance.set_node_value(Some(to_string(content).as_str()));
     }
+
+    fn get_document(&self) -> Document {
+        let window = window().expect("no global `window` exists");
+        window.document().expect("should have a document on window")
+    }
+
+    fn append_to_head(&self, document: &Document, element: &Element) {
+        let head = derive_from_js_value(document, "head");
+        if let Ok(head) = head.dyn_into::<Node>() {
+            let _ = head.append_child(element);
+        }
+    }
+
+    fn has_module_script(&self, document: &Document, href: &str) -> bool {
+        let selector = format!(
+            "script[type=\"module\"][src=\"{}\"]",
+            escape_selector_value(href)
+        );
+        matches!(document.query_selector(selector.as_str()), Ok(Some(_)))
+    }
+
+    // Inserts <script type="module"> once per href, only module scripts can be preinitialized
+    pub fn preinit_module(&self, href: &str, options: &JsValue) {
+        let options = &normalize_options(options);
+        let _as = derive_from_js_value(options, "as");
+        if href.is_empty() || (!_as.is_undefined() && _as.as_string().as_deref() != Some("script"))
+        {
+            return;
+        }
+        let document = self.get_document();
+        if self.has_module_script(&document, href) {
+            return;
+        }
+        let script = document.create_element("script").unwrap();
+        let _ = script.set_attribute("type", "module");
+        let _ = script.set_attribute("src", href);
+        let _ = script.set_attribute("async", "");
+        set_string_attribute(&script, "crossorigin", options, "crossOrigin");
+        set_string_attribute(&script, "integrity", options, "integrity");
+        set_string_attribute(&script, "nonce", options, "nonce");
+        self.append_to_head(&document, &script);
+    }
+
+    // Inserts <link rel="modulepreload"> once per href, unless the module script already exists
+    pub fn preload_module(&self, href: &str, options: &JsValue) {
+        let options = &normalize_options(options);
+        if href.is_empty() {
+            return;
+        }
+        let document = self.get_document();
+        let selector = format!(
+            "link[rel=\"modulepreload\"][href=\"{}\"]",
+            escape_selector_value(href)
+        );
+        if matches!(document.query_selector(selector.as_str()), Ok(Some(_)))
+            || self.has_module_script(&document, href)
+        {
+            return;
+        }
+        let link = document.create_element("link").unwrap();
+        let _ = link.set_attribute("rel", "modulepreload");
+        let _ = link.set_attribute("href", href);
+        if let Some(_as) = derive_from_js_value(options, "as").as_string() {
+            if _as != "script" {
+                let _ = link.set_attribute("as", _as.as_str());
+            }
+        }
+        set_string_attribute(&link, "crossorigin", options, "crossOrigin");
+        set_string_attribute(&link, "integrity", options, "integrity");
+        self.append_to_head(&document, &link);
+    }
 }
 
 impl HostConfig for ReactDomHostConfig {
@@ -83,6 +175,36 @@ impl HostConfig for ReactDomHostConfig {
         }
     }
 
+    fn finalize_initial_children(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        props: &JsValue,
+    ) -> bool {
+        let node = instance.downcast::<Node>().unwrap();
+        set_initial_properties(node.dyn_ref::<Element>().unwrap(), props);
+        match _type.as_str() {
+            "button" | "input" | "select" | "textarea" => {
+                derive_from_js_value(props, "autoFocus").is_truthy()
+            }
+            _ => false,
+        }
+    }
+
+    fn commit_mount(&self, instance: Rc<dyn Any>, _type: String, props: &JsValue) {
+        match _type.as_str() {
+            "button" | "input" | "select" | "textarea" => {
+                if derive_from_js_value(props, "autoFocus").is_truthy() {
+                    let node = instance.downcast::<Node>().unwrap();
+                    if let Some(element) = node.dyn_ref::<HtmlElement>() {
+                        element.focus().expect("failed to focus");
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
         let p = parent.clone().downcast::<Node>().unwrap();
         let c = child.clone().downcast::<Node>().unwrap();
@@ -203,6 +325,64 @@ impl HostConfig for ReactDomHostConfig {
         }
     }
 
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<HtmlElement>() {
+            let _ = element
+                .style()
+                .set_property_with_priority("display", "none", "important");
+        }
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>, props: &JsValue) {
+        let instance = instance.clone().downcast::<Node>().unwrap();
+        if let Some(element) = instance.dyn_ref::<HtmlElement>() {
+            let style = derive_from_js_value(props, "style");
+            let display = if style.is_object() {
+                derive_from_js_value(&style, "display")
+            } else {
+                JsValue::undefined()
+            };
+            let display = if display.is_undefined() || display.is_null() {
+                "".to_string()
+            } else {
+                to_string(&display)
+            };
+            let _ = element.style().set_property("display", display.as_str());
+        }
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        let text_instance = text_instance.clone().downcast::<Node>().unwrap();
+        text_instance.set_node_value(Some(""));
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: &JsValue) {
+        self.commit_text_update(text_instance, content);
+    }
+
+    // The container was checked by createPortal
+    fn prepare_portal_mount(&self, container_info: &JsValue) -> Rc<dyn Any> {
+        Rc::new(container_info.clone().unchecked_into::<Node>())
+    }
+
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> JsValue {
+        let node = instance.downcast::<Node>().unwrap();
+        (*node).clone().into()
+    }
+
+    // The DOM writes of the mutation phase don't cause a reflow until the layout is read,
+    // which happens in the layout phase at the earliest
+    fn prepare_for_commit(&self, _container: Rc<dyn Any>) {}
+
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {}
+
+    // The event handlers kept on the node would otherwise live as long as the node does
+    fn detach_deleted_instance(&self, instance: Rc<dyn Any>) {
+        let node = instance.downcast::<Node>().unwrap();
+        detach_fiber_props(&(*node).clone().into());
+    }
+
     fn commit_update(&self, fiber: Rc<RefCell<FiberNode>>) {
         let instance = FiberNode::derive_state_node(fiber.clone());
         let memoized_props = fiber.borrow().memoized_props.clone();
@@ -212,15 +392,14 @@ impl HostConfig for ReactDomHostConfig {
                 self.commit_text_update(instance.unwrap(), &text);
             }
             WorkTag::HostComponent => {
-                update_fiber_props(
-                    instance
-                        .unwrap()
-                        .downcast::<Node>()
-                        .unwrap()
-                        .dyn_ref::<Element>()
-                        .unwrap(),
-                    &memoized_props,
-                );
+                let node = instance.unwrap().downcast::<Node>().unwrap();
+                let element = node.dyn_ref::<Element>().unwrap();
+                update_fiber_props(element, &memoized_props);
+                let current = { fiber.borrow().alternate.clone() };
+                if let Some(current) = current {
+                    let last_props = { current.borrow().memoized_props.clone() };
+                    update_properties(element, &last_props, &memoized_props);
+                }
             }
             _ => {
                 log!("Unsupported update type")
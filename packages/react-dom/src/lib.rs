@@ -1,40 +1,50 @@
 use gloo::console::log;
-use js_sys::{Array, Function, Object, Reflect};
+use js_sys::{Array, Error, Function, Object, Reflect};
 use react_reconciler::fiber::FiberRootNode;
+use react_reconciler::fiber_lanes::{self, Lane};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
-use web_sys::Node;
+use web_sys::{Element, Node};
 
 use react_reconciler::Reconciler;
 use scheduler::{
-    unstable_cancel_callback, unstable_schedule_callback as origin_unstable_schedule_callback,
-    unstable_should_yield_to_host, Priority,
+    unstable_cancel_callback, unstable_now as origin_unstable_now, unstable_request_paint,
+    unstable_schedule_callback as origin_unstable_schedule_callback, unstable_should_yield_to_host,
+    Priority, Task,
 };
+use shared::{derive_from_js_value, to_string, FEATURE_FLAGS, REACT_PORTAL_TYPE, REACT_VERSION};
 
 use crate::host_config::ReactDomHostConfig;
 use crate::renderer::Renderer;
+use crate::synthetic_event::init_event;
 use crate::utils::set_panic_hook;
 
+mod dom_component;
 mod host_config;
 mod renderer;
 mod synthetic_event;
 mod utils;
 
 // static mut CONTAINER_TO_ROOT: Option<HashMap<JsValue, Rc<RefCell<FiberRootNode>>>> = None;
+static mut LEGACY_ROOTS: Vec<(Node, Rc<RefCell<FiberRootNode>>)> = vec![];
+
+// TODO: there is no server renderer yet, neither render_to_string nor a server host config.
+// render_to_readable_stream would build on one: it writes the shell first with the fallbacks of
+// the suspended boundaries between <!--$?--> markers, then a chunk per resolved boundary with
+// its content and an inline script that swaps it in on the client.
 
 #[wasm_bindgen(js_name = createRoot)]
-pub fn create_root(container: &JsValue) -> Renderer {
+pub fn create_root(container: &JsValue) -> Result<Renderer, JsValue> {
     set_panic_hook();
+    // Thrown to the caller, instead of a panic once the container is used
+    if !container.is_instance_of::<Element>() {
+        return Err(Error::new("createRoot(...): Target container is not a DOM element.").into());
+    }
     let reconciler = Reconciler::new(Rc::new(ReactDomHostConfig));
-    let node = match container.clone().dyn_into::<Node>() {
-        Ok(node) => node,
-        Err(_) => {
-            panic!("container should be Node")
-        }
-    };
+    let node = container.clone().unchecked_into::<Node>();
 
     // TODO cache the container
     // let mut root;
@@ -51,7 +61,151 @@ pub fn create_root(container: &JsValue) -> Renderer {
     //     CONTAINER_TO_ROOT.unwrap().insert(container.clone(), root);
     // }
 
+    // The listeners are attached once, the root is rendered into any number of times
+    init_event(container.clone(), "click".to_string());
     let root = reconciler.create_container(Rc::new(node));
-    let renderer = Renderer::new(root, reconciler, container);
-    renderer
+    let renderer = Renderer::new(root, reconciler);
+    Ok(renderer)
+}
+
+#[wasm_bindgen(js_name = flushSync)]
+pub fn flush_sync(f: &Function) {
+    react_reconciler::flush_sync(f)
+}
+
+#[wasm_bindgen(js_name = unstable_batchedUpdates)]
+pub fn batched_updates(f: &Function, a: &JsValue) -> Result<JsValue, JsValue> {
+    react_reconciler::batched_updates(|a| f.call1(&JsValue::null(), a), a)
+}
+
+#[wasm_bindgen]
+pub fn render(
+    element: &JsValue,
+    container: &JsValue,
+    callback: &JsValue,
+) -> Result<JsValue, JsValue> {
+    set_panic_hook();
+    let reconciler = Reconciler::new(Rc::new(ReactDomHostConfig));
+    let node = match container.clone().dyn_into::<Node>() {
+        Ok(node) => node,
+        Err(_) => {
+            return Err(Error::new("render(...): Target container is not a DOM element.").into())
+        }
+    };
+
+    let root = unsafe {
+        match LEGACY_ROOTS
+            .iter()
+            .find(|(container_node, _)| container_node.is_same_node(Some(&node)))
+        {
+            Some((_, root)) => root.clone(),
+            None => {
+                init_event(container.clone(), "click".to_string());
+                let root = reconciler.create_container(Rc::new(node.clone()));
+                LEGACY_ROOTS.push((node, root.clone()));
+                root
+            }
+        }
+    };
+
+    let callback = callback.dyn_ref::<Function>().cloned();
+    reconciler.update_container_with_callback(element.clone(), root, callback);
+    Ok(JsValue::undefined())
+}
+
+#[wasm_bindgen(js_name = createPortal)]
+pub fn create_portal(
+    children: &JsValue,
+    container: &JsValue,
+    key: &JsValue,
+) -> Result<JsValue, JsValue> {
+    // Thrown to the caller, the container is used without a check once the portal is mounted
+    if !container.is_instance_of::<Node>() {
+        return Err(Error::new("createPortal(...): Target container is not a DOM element.").into());
+    }
+
+    let portal = Object::new();
+    Reflect::set(
+        &portal,
+        &"$$typeof".into(),
+        &JsValue::from_str(REACT_PORTAL_TYPE),
+    )
+    .expect("$$typeof panic");
+    let key = if key.is_undefined() || key.is_null() {
+        JsValue::null()
+    } else {
+        JsValue::from_str(&to_string(key))
+    };
+    Reflect::set(&portal, &"key".into(), &key).expect("key panic");
+    Reflect::set(&portal, &"children".into(), children).expect("children panic");
+    Reflect::set(&portal, &"containerInfo".into(), container).expect("containerInfo panic");
+    Ok(portal.into())
+}
+
+#[wasm_bindgen(js_name = preinitModule)]
+pub fn preinit_module(href: &str, options: &JsValue) {
+    ReactDomHostConfig.preinit_module(href, options)
+}
+
+#[wasm_bindgen(js_name = preloadModule)]
+pub fn preload_module(href: &str, options: &JsValue) {
+    ReactDomHostConfig.preload_module(href, options)
+}
+
+// TODO: requestFormReset needs form actions first. The event system only dispatches click, there
+// is no submit handling of <form action={fn}> and no useActionState. With them the reset is an
+// update of the form's host transition hook, so form.reset() runs when the action commits.
+
+#[wasm_bindgen]
+pub fn version() -> String {
+    REACT_VERSION.to_string()
+}
+
+#[wasm_bindgen(js_name = getFeatureFlags)]
+pub fn get_feature_flags() -> JsValue {
+    let feature_flags = Object::new();
+    for (name, enabled) in FEATURE_FLAGS.iter() {
+        Reflect::set(&feature_flags, &(*name).into(), &(*enabled).into())
+            .expect("feature flag panic");
+    }
+    feature_flags.into()
+}
+
+// The priority is the lane of the updates, e.g. 1 for SyncLane, 0 to reset it
+#[wasm_bindgen(js_name = unstable_setCurrentUpdatePriority)]
+pub fn set_current_update_priority(priority: u32) {
+    fiber_lanes::set_current_update_priority(Lane::from_bits_truncate(priority))
+}
+
+#[wasm_bindgen(js_name = unstable_getCurrentUpdatePriority)]
+pub fn get_current_update_priority() -> u32 {
+    fiber_lanes::get_current_update_priority().bits()
+}
+
+// The scheduler of the renderer, so that the callbacks are interleaved with its work
+#[wasm_bindgen]
+pub fn unstable_now() -> f64 {
+    origin_unstable_now()
+}
+
+#[wasm_bindgen(js_name = unstable_requestPaint)]
+pub fn request_paint() {
+    unstable_request_paint()
+}
+
+#[wasm_bindgen(js_name = unstable_scheduleCallback)]
+pub fn schedule_callback(priority_level: Priority, callback: Function, options: &JsValue) -> Task {
+    let delay = if options.is_object() {
+        derive_from_js_value(options, "delay")
+            .as_f64()
+            .unwrap_or(0.0)
+    } else {
+        0.0
+    };
+    origin_unstable_schedule_callback(priority_level, callback, delay)
+}
+
+#[wasm_bindgen(js_name = unstable_cancelCallback)]
+pub fn cancel_callback(task: &Task) {
+    unstable_cancel_callback(task.clone())
 }
@@ -8,26 +8,15 @@ use react_reconciler::fiber::FiberRootNode;
 use react_reconciler::Reconciler;
 use web_sys::Element;
 
-use crate::synthetic_event::init_event;
-
 #[wasm_bindgen]
 pub struct Renderer {
-    container: JsValue,
     root: Rc<RefCell<FiberRootNode>>,
     reconciler: Reconciler,
 }
 
 impl Renderer {
-    pub fn new(
-        root: Rc<RefCell<FiberRootNode>>,
-        reconciler: Reconciler,
-        container: &JsValue,
-    ) -> Self {
-        Self {
-            root,
-            reconciler,
-            container: container.clone(),
-        }
+    pub fn new(root: Rc<RefCell<FiberRootNode>>, reconciler: Reconciler) -> Self {
+        Self { root, reconciler }
     }
 
     // fn clear_container_dom(&self) {
@@ -42,14 +31,13 @@ impl Renderer {
 
 #[wasm_bindgen]
 impl Renderer {
+    // Rendering null deletes the tree but keeps the root, unlike unmount
     pub fn render(&self, element: &JsValue) -> JsValue {
-        init_event(self.container.clone(), "click".to_string());
         self.reconciler
             .update_container(element.clone(), self.root.clone())
     }
 
     pub fn unmount(&self) -> JsValue {
-        self.reconciler
-            .update_container(JsValue::null(), self.root.clone())
+        self.reconciler.unmount_container(self.root.clone())
     }
 }
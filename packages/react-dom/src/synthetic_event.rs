@@ -10,6 +10,8 @@ use shared::{derive_from_js_value, is_dev, log};
 
 static VALID_EVENT_TYPE_LIST: [&str; 1] = ["click"];
 static ELEMENT_EVENT_PROPS_KEY: &str = "__props";
+// Set on the container of every root, to the event types that are listened to on it
+static CONTAINER_LISTENING_KEY: &str = "__reactListening";
 
 struct Paths {
     capture: Vec<Function>,
@@ -114,6 +116,11 @@ fn collect_paths(
 ) -> Paths {
     let mut paths = Paths::new();
     while target_element.is_some() && !Object::is(target_element.as_ref().unwrap(), container) {
+        // The container of a root rendered inside this one, its listener dispatches to the
+        // handlers below it and this root only handles the ones above
+        if is_root_container(target_element.as_ref().unwrap()) {
+            paths = Paths::new();
+        }
         let event_props =
             derive_from_js_value(target_element.as_ref().unwrap(), ELEMENT_EVENT_PROPS_KEY);
         if event_props.is_object() {
@@ -144,12 +151,32 @@ fn get_event_callback_name_from_event_type(event_type: &str) -> Option<Vec<&str>
     None
 }
 
+fn is_root_container(element: &Element) -> bool {
+    derive_from_js_value(element, CONTAINER_LISTENING_KEY).is_object()
+}
+
 pub fn init_event(container: JsValue, event_type: String) {
     if !VALID_EVENT_TYPE_LIST.contains(&event_type.clone().as_str()) {
         log!("Unsupported event type: {:?}", event_type);
         return;
     }
 
+    // Another root created on the same container shares its listener
+    let listening = derive_from_js_value(&container, CONTAINER_LISTENING_KEY);
+    let listening = if listening.is_object() {
+        listening
+    } else {
+        let listening = Object::new().into();
+        Reflect::set(&container, &CONTAINER_LISTENING_KEY.into(), &listening)
+            .expect("TODO: panic set CONTAINER_LISTENING_KEY");
+        listening
+    };
+    if derive_from_js_value(&listening, event_type.as_str()).is_truthy() {
+        return;
+    }
+    Reflect::set(&listening, &event_type.as_str().into(), &JsValue::TRUE)
+        .expect("TODO: panic set listening event type");
+
     if is_dev() {
         log!("Init event {:?}", event_type);
     }
@@ -164,6 +191,10 @@ pub fn init_event(container: JsValue, event_type: String) {
     on_click.forget();
 }
 
+pub fn detach_fiber_props(node: &JsValue) {
+    let _ = Reflect::delete_property(&Object::from(node.clone()), &ELEMENT_EVENT_PROPS_KEY.into());
+}
+
 pub fn update_fiber_props(node: &Element, props: &JsValue) {
     // log!("update_fiber_props {:?}", node);
     let js_value = derive_from_js_value(&node, ELEMENT_EVENT_PROPS_KEY);
@@ -14,6 +14,10 @@ use react_reconciler::HostConfig;
 use shared::{derive_from_js_value, log};
 
 static mut INSTANCE_COUNTER: u32 = 0;
+static mut IS_COMMITTING: bool = false;
+// (commits, text updates, text updates applied outside of a commit)
+static mut COMMIT_STATS: (u32, u32, u32) = (0, 0, 0);
+static mut DETACHED_INSTANCES: Vec<JsValue> = vec![];
 
 pub struct ReactNoopHostConfig;
 
@@ -63,6 +67,24 @@ fn getCounter() -> u32 {
     counter
 }
 
+pub fn get_commit_stats() -> JsValue {
+    let (commits, text_updates, text_updates_outside_commit) = unsafe { COMMIT_STATS };
+    let stats = Object::new();
+    Reflect::set(&stats, &"commits".into(), &JsValue::from(commits));
+    Reflect::set(&stats, &"textUpdates".into(), &JsValue::from(text_updates));
+    Reflect::set(
+        &stats,
+        &"textUpdatesOutsideCommit".into(),
+        &JsValue::from(text_updates_outside_commit),
+    );
+    stats.into()
+}
+
+pub fn take_detached_instances() -> JsValue {
+    let instances = unsafe { std::mem::take(&mut DETACHED_INSTANCES) };
+    instances.into_iter().collect::<Array>().into()
+}
+
 pub fn create_container() -> JsValue {
     let container = Object::new();
     Reflect::set(&container, &"rootId".into(), &JsValue::from(getCounter()));
@@ -73,6 +95,12 @@ pub fn create_container() -> JsValue {
 
 impl ReactNoopHostConfig {
     fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: &JsValue) {
+        unsafe {
+            COMMIT_STATS.1 += 1;
+            if !IS_COMMITTING {
+                COMMIT_STATS.2 += 1;
+            }
+        }
         let text_instance = text_instance.clone().downcast::<JsValue>().unwrap();
         Reflect::set(&text_instance, &"text".into(), content);
     }
@@ -101,6 +129,17 @@ impl HostConfig for ReactNoopHostConfig {
         Rc::new(JsValue::from(obj))
     }
 
+    fn finalize_initial_children(
+        &self,
+        _instance: Rc<dyn Any>,
+        _type: String,
+        _props: &JsValue,
+    ) -> bool {
+        false
+    }
+
+    fn commit_mount(&self, _instance: Rc<dyn Any>, _type: String, _props: &JsValue) {}
+
     fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>) {
         let p = parent.clone().downcast::<JsValue>().unwrap();
         let c = child.clone().downcast::<JsValue>().unwrap();
@@ -161,7 +200,7 @@ impl HostConfig for ReactNoopHostConfig {
         }
         let before = before.clone().downcast::<JsValue>().unwrap();
         let before_index = children.index_of(&before, 0);
-        if before_index != -1 {
+        if before_index == -1 {
             panic!("Before does not exist")
         }
 
@@ -214,6 +253,49 @@ impl HostConfig for ReactNoopHostConfig {
         }
     }
 
+    fn hide_instance(&self, instance: Rc<dyn Any>) {
+        let instance = instance.clone().downcast::<JsValue>().unwrap();
+        Reflect::set(&instance, &"hidden".into(), &true.into());
+    }
+
+    fn unhide_instance(&self, instance: Rc<dyn Any>, _props: &JsValue) {
+        let instance = instance.clone().downcast::<JsValue>().unwrap();
+        Reflect::set(&instance, &"hidden".into(), &false.into());
+    }
+
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>) {
+        self.hide_instance(text_instance);
+    }
+
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: &JsValue) {
+        self.commit_text_update(text_instance.clone(), content);
+        self.unhide_instance(text_instance, &JsValue::null());
+    }
+
+    fn prepare_portal_mount(&self, container_info: &JsValue) -> Rc<dyn Any> {
+        Rc::new(container_info.clone())
+    }
+
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> JsValue {
+        (*instance.downcast::<JsValue>().unwrap()).clone()
+    }
+
+    fn prepare_for_commit(&self, _container: Rc<dyn Any>) {
+        unsafe {
+            IS_COMMITTING = true;
+            COMMIT_STATS.0 += 1;
+        }
+    }
+
+    fn reset_after_commit(&self, _container: Rc<dyn Any>) {
+        unsafe { IS_COMMITTING = false }
+    }
+
+    fn detach_deleted_instance(&self, instance: Rc<dyn Any>) {
+        let instance = (*instance.downcast::<JsValue>().unwrap()).clone();
+        unsafe { DETACHED_INSTANCES.push(instance) }
+    }
+
     fn commit_update(&self, fiber: Rc<RefCell<FiberNode>>) {
         match fiber.borrow().tag {
             WorkTag::HostText => {
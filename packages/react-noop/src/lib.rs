@@ -21,4 +21,16 @@ pub fn create_root() -> Renderer {
     let root = reconciler.create_container(Rc::new(container.clone()));
     let renderer = Renderer::new(root, reconciler, container);
     renderer
-}
\ No newline at end of file
+}
+
+// How many commits and text updates the host config has seen, for testing the commit windows
+#[wasm_bindgen(js_name = getCommitStats)]
+pub fn get_commit_stats() -> JsValue {
+    host_config::get_commit_stats()
+}
+
+// The instances passed to detach_deleted_instance since the last call
+#[wasm_bindgen(js_name = takeDetachedInstances)]
+pub fn take_detached_instances() -> JsValue {
+    host_config::take_detached_instances()
+}
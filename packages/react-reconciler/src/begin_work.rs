@@ -3,15 +3,18 @@ use std::rc::Rc;
 
 use wasm_bindgen::{JsCast, JsValue};
 
-use shared::{derive_from_js_value, is_dev, log, shallow_equal};
-use web_sys::js_sys::{Function, Object};
+use shared::{derive_from_js_value, error, is_dev, log, shallow_equal};
+use web_sys::js_sys::{Function, Object, Reflect};
 
 use crate::child_fiber::{clone_child_fiblers, mount_child_fibers, reconcile_child_fibers};
 use crate::fiber::{FiberNode, MemoizedState};
-use crate::fiber_context::{prepare_to_read_context, propagate_context_change, push_provider};
+use crate::fiber_context::{
+    prepare_to_read_context, propagate_context_change, push_provider, read_context,
+};
 use crate::fiber_flags::Flags;
 use crate::fiber_hooks::{bailout_hook, render_with_hooks};
 use crate::fiber_lanes::{include_some_lanes, Lane};
+use crate::suspense_context::push_suspense_handler;
 use crate::update_queue::{process_update_queue, ReturnOfProcessUpdateQueue};
 use crate::work_tags::WorkTag;
 
@@ -62,6 +65,8 @@ pub fn begin_work(
         let old_type = current.borrow()._type.clone();
         let new_props = work_in_progress.borrow().pending_props.clone();
         let new_type = work_in_progress.borrow()._type.clone();
+        // The props are the same object when the parent bailed out and cloned this fiber, or
+        // rendered the same element again, which bails out without comparing them key by key
         if !Object::is(&old_props, &new_props) || !Object::is(&old_type, &new_type) {
             unsafe { DID_RECEIVE_UPDATE = true }
         } else {
@@ -69,7 +74,9 @@ pub fn begin_work(
                 check_scheduled_update_or_context(current.clone(), render_lane.clone());
             // The current fiber lane is not included in render_lane
             // TODO context
-            if !has_scheduled_update_or_context {
+            if !has_scheduled_update_or_context
+                && !work_in_progress.borrow().flags.contains(Flags::DidCapture)
+            {
                 unsafe { DID_RECEIVE_UPDATE = false }
                 // // if current.is_some() {
                 // let c = current.clone();
@@ -91,6 +98,9 @@ pub fn begin_work(
                             derive_from_js_value(&work_in_progress.borrow()._type, "_context");
                         push_provider(&context, new_value);
                     }
+                    WorkTag::SuspenseComponent => {
+                        push_suspense_handler(work_in_progress.clone());
+                    }
                     _ => {}
                 }
                 return Ok(bailout_on_already_finished_work(
@@ -115,47 +125,307 @@ pub fn begin_work(
         }
         WorkTag::HostRoot => Ok(update_host_root(work_in_progress.clone(), render_lane)),
         WorkTag::HostComponent => Ok(update_host_component(work_in_progress.clone())),
+        // A text node has no children, its instance is created in complete_work
         WorkTag::HostText => Ok(None),
+        WorkTag::HostPortal => Ok(update_portal_component(work_in_progress.clone())),
         WorkTag::ContextProvider => Ok(update_context_provider(
             work_in_progress.clone(),
             render_lane.clone(),
         )),
+        WorkTag::ContextConsumer => {
+            update_context_consumer(work_in_progress.clone(), render_lane.clone())
+        }
         WorkTag::MemoComponent => update_memo_component(work_in_progress.clone(), render_lane),
+        WorkTag::SimpleMemoComponent => {
+            update_simple_memo_component(work_in_progress.clone(), render_lane)
+        }
         WorkTag::Fragment => Ok(update_fragment(work_in_progress.clone())),
+        WorkTag::Mode => Ok(update_mode(work_in_progress.clone())),
+        WorkTag::SuspenseComponent => Ok(update_suspense_component(work_in_progress.clone())),
+        WorkTag::OffscreenComponent => Ok(update_offscreen_component(work_in_progress.clone())),
     };
 }
 
+fn update_suspense_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let current = { work_in_progress.borrow().alternate.clone() };
+    let next_props = { work_in_progress.borrow().pending_props.clone() };
+
+    let mut show_fallback = false;
+    let did_suspend = work_in_progress.borrow().flags.contains(Flags::DidCapture);
+    if did_suspend {
+        show_fallback = true;
+        work_in_progress.borrow_mut().flags -= Flags::DidCapture;
+    }
+
+    let next_primary_children = derive_from_js_value(&next_props, "children");
+    let next_fallback_children = derive_from_js_value(&next_props, "fallback");
+    push_suspense_handler(work_in_progress.clone());
+
+    if current.is_none() {
+        if show_fallback {
+            Some(mount_suspense_fallback_children(
+                work_in_progress.clone(),
+                next_primary_children,
+                next_fallback_children,
+            ))
+        } else {
+            Some(mount_suspense_primary_children(
+                work_in_progress.clone(),
+                next_primary_children,
+            ))
+        }
+    } else {
+        if show_fallback {
+            Some(update_suspense_fallback_children(
+                work_in_progress.clone(),
+                next_primary_children,
+                next_fallback_children,
+            ))
+        } else {
+            Some(update_suspense_primary_children(
+                work_in_progress.clone(),
+                next_primary_children,
+            ))
+        }
+    }
+}
+
+fn create_offscreen_props(mode: &str, children: JsValue) -> JsValue {
+    let props = Object::new();
+    Reflect::set(&props, &"mode".into(), &mode.into()).expect("props panic");
+    Reflect::set(&props, &"children".into(), &children).expect("props panic");
+    props.into()
+}
+
+fn mount_suspense_primary_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: JsValue,
+) -> Rc<RefCell<FiberNode>> {
+    let mode = { work_in_progress.borrow().mode.clone() };
+    let primary_child_props = create_offscreen_props("visible", primary_children);
+    let primary_child_fragment = Rc::new(RefCell::new(FiberNode::create_fiber_from_offscreen(
+        primary_child_props,
+        mode,
+    )));
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment.clone());
+    primary_child_fragment
+}
+
+fn mount_suspense_fallback_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: JsValue,
+    fallback_children: JsValue,
+) -> Rc<RefCell<FiberNode>> {
+    let mode = { work_in_progress.borrow().mode.clone() };
+    let primary_child_props = create_offscreen_props("hidden", primary_children);
+    let primary_child_fragment = Rc::new(RefCell::new(FiberNode::create_fiber_from_offscreen(
+        primary_child_props,
+        mode.clone(),
+    )));
+    let fallback_child_fragment = Rc::new(RefCell::new(FiberNode::create_fiber_from_fragment(
+        fallback_children,
+        JsValue::null(),
+        mode,
+    )));
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    fallback_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    primary_child_fragment.borrow_mut().sibling = Some(fallback_child_fragment.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment);
+    fallback_child_fragment
+}
+
+// The offscreen fiber of current is always reused, only its mode changes between
+// visible and hidden, so the state of the primary children is preserved
+fn update_suspense_primary_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: JsValue,
+) -> Rc<RefCell<FiberNode>> {
+    let current = { work_in_progress.borrow().alternate.clone().unwrap() };
+    let current_primary_child_fragment = { current.borrow().child.clone().unwrap() };
+    let current_fallback_child_fragment =
+        { current_primary_child_fragment.borrow().sibling.clone() };
+
+    let primary_child_props = create_offscreen_props("visible", primary_children);
+    let primary_child_fragment =
+        FiberNode::create_work_in_progress(current_primary_child_fragment, primary_child_props);
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    primary_child_fragment.borrow_mut().sibling = None;
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment.clone());
+
+    if current_fallback_child_fragment.is_some() {
+        let mut wip = work_in_progress.borrow_mut();
+        if wip.deletions.is_empty() {
+            wip.deletions = vec![current_fallback_child_fragment.unwrap()];
+            wip.flags |= Flags::ChildDeletion;
+        } else {
+            wip.deletions.push(current_fallback_child_fragment.unwrap());
+        }
+    }
+
+    primary_child_fragment
+}
+
+fn update_suspense_fallback_children(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    primary_children: JsValue,
+    fallback_children: JsValue,
+) -> Rc<RefCell<FiberNode>> {
+    let current = { work_in_progress.borrow().alternate.clone().unwrap() };
+    let current_primary_child_fragment = { current.borrow().child.clone().unwrap() };
+    let current_fallback_child_fragment =
+        { current_primary_child_fragment.borrow().sibling.clone() };
+
+    let primary_child_props = create_offscreen_props("hidden", primary_children);
+    let primary_child_fragment =
+        FiberNode::create_work_in_progress(current_primary_child_fragment, primary_child_props);
+
+    let fallback_child_fragment;
+    if current_fallback_child_fragment.is_some() {
+        fallback_child_fragment = FiberNode::create_work_in_progress(
+            current_fallback_child_fragment.unwrap(),
+            fallback_children,
+        );
+    } else {
+        fallback_child_fragment = Rc::new(RefCell::new(FiberNode::create_fiber_from_fragment(
+            fallback_children,
+            JsValue::null(),
+            work_in_progress.borrow().mode.clone(),
+        )));
+        fallback_child_fragment.borrow_mut().flags |= Flags::Placement;
+    }
+
+    fallback_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    fallback_child_fragment.borrow_mut().sibling = None;
+    primary_child_fragment.borrow_mut()._return = Some(work_in_progress.clone());
+    primary_child_fragment.borrow_mut().sibling = Some(fallback_child_fragment.clone());
+    work_in_progress.borrow_mut().child = Some(primary_child_fragment);
+
+    fallback_child_fragment
+}
+
+fn update_offscreen_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let next_props = { work_in_progress.borrow().pending_props.clone() };
+    let next_children = derive_from_js_value(&next_props, "children");
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.borrow().child.clone()
+}
+
 fn update_fragment(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
     let next_children = work_in_progress.borrow().pending_props.clone();
     reconcile_children(work_in_progress.clone(), Some(next_children));
     work_in_progress.borrow().child.clone()
 }
 
+// StrictMode only changes the mode of its subtree, which happens when its children are created
+fn update_mode(work_in_progress: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let next_props = { work_in_progress.borrow().pending_props.clone() };
+    let next_children = derive_from_js_value(&next_props, "children");
+    reconcile_children(work_in_progress.clone(), Some(next_children));
+    work_in_progress.borrow().child.clone()
+}
+
+fn update_portal_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+) -> Option<Rc<RefCell<FiberNode>>> {
+    let next_children = { work_in_progress.borrow().pending_props.clone() };
+    let current = { work_in_progress.borrow().alternate.clone() };
+    if current.is_none() {
+        // The children of a portal aren't appended to its host parent during mount,
+        // so the insertions are tracked to place them into the portal container
+        work_in_progress.borrow_mut().child =
+            reconcile_child_fibers(work_in_progress.clone(), None, Some(next_children));
+    } else {
+        reconcile_children(work_in_progress.clone(), Some(next_children));
+    }
+    work_in_progress.borrow().child.clone()
+}
+
+fn memo_props_equal(memo_type: &JsValue, prev_props: &JsValue, next_props: &JsValue) -> bool {
+    let compare = derive_from_js_value(memo_type, "compare");
+    if compare.is_function() {
+        let f = compare.dyn_ref::<Function>().unwrap();
+        return f
+            .call2(&JsValue::null(), prev_props, next_props)
+            .unwrap()
+            .as_bool()
+            .unwrap();
+    }
+    shallow_equal(prev_props, next_props)
+}
+
 fn update_memo_component(
     work_in_progress: Rc<RefCell<FiberNode>>,
     render_lane: Lane,
 ) -> Result<Option<Rc<RefCell<FiberNode>>>, JsValue> {
     let current = { work_in_progress.borrow().alternate.clone() };
+    let memo_type = { work_in_progress.borrow()._type.clone() };
+    let next_props = { work_in_progress.borrow().pending_props.clone() };
+    let Component = derive_from_js_value(&memo_type, "type");
+
+    if current.is_none() {
+        // Without a comparator a memo around a plain function doesn't need a fiber of its own,
+        // the inner component is rendered in this fiber and the tag sticks for its updates
+        if FiberNode::is_simple_function_component(&Component)
+            && !derive_from_js_value(&memo_type, "compare").is_function()
+        {
+            work_in_progress.borrow_mut().tag = WorkTag::SimpleMemoComponent;
+            return update_simple_memo_component(work_in_progress, render_lane);
+        }
+        let _ref = { work_in_progress.borrow()._ref.clone() };
+        let mode = { work_in_progress.borrow().mode.clone() };
+        let child = Rc::new(RefCell::new(FiberNode::create_fiber_from_type_and_props(
+            Component,
+            JsValue::null(),
+            next_props,
+            _ref,
+            mode,
+        )));
+        child.borrow_mut()._return = Some(work_in_progress.clone());
+        work_in_progress.borrow_mut().child = Some(child.clone());
+        return Ok(Some(child));
+    }
+
+    let current = current.unwrap();
+    let current_child = { current.borrow().child.clone().unwrap() };
+    if !check_scheduled_update_or_context(current.clone(), render_lane.clone()) {
+        let prev_props = { current_child.borrow().memoized_props.clone() };
+        if memo_props_equal(&memo_type, &prev_props, &next_props)
+            && Object::is(&current.borrow()._ref, &work_in_progress.borrow()._ref)
+        {
+            return Ok(bailout_on_already_finished_work(
+                work_in_progress.clone(),
+                render_lane,
+            ));
+        }
+    }
+
+    let new_child = FiberNode::create_work_in_progress(current_child, next_props);
+    new_child.borrow_mut()._ref = work_in_progress.borrow()._ref.clone();
+    new_child.borrow_mut()._return = Some(work_in_progress.clone());
+    work_in_progress.borrow_mut().child = Some(new_child.clone());
+    Ok(Some(new_child))
+}
+
+fn update_simple_memo_component(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    render_lane: Lane,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, JsValue> {
+    let current = { work_in_progress.borrow().alternate.clone() };
+    let memo_type = { work_in_progress.borrow()._type.clone() };
     let next_props = { work_in_progress.borrow().pending_props.clone() };
 
     if current.is_some() {
         let current = current.unwrap();
         let prev_props = current.borrow().memoized_props.clone();
         if !check_scheduled_update_or_context(current.clone(), render_lane.clone()) {
-            let mut props_equal = false;
-            let compare = derive_from_js_value(&work_in_progress.borrow()._type, "compare");
-            if compare.is_function() {
-                let f = compare.dyn_ref::<Function>().unwrap();
-                props_equal = f
-                    .call2(&JsValue::null(), &prev_props, &next_props)
-                    .unwrap()
-                    .as_bool()
-                    .unwrap();
-            } else {
-                props_equal = shallow_equal(&prev_props, &next_props);
-            }
-
-            if props_equal && Object::is(&current.borrow()._ref, &work_in_progress.borrow()._ref) {
+            if shallow_equal(&prev_props, &next_props)
+                && Object::is(&current.borrow()._ref, &work_in_progress.borrow()._ref)
+            {
                 unsafe { DID_RECEIVE_UPDATE = false };
                 work_in_progress.borrow_mut().pending_props = prev_props;
                 work_in_progress.borrow_mut().lanes = current.borrow().lanes.clone();
@@ -166,7 +436,7 @@ fn update_memo_component(
             }
         }
     }
-    let Component = { derive_from_js_value(&work_in_progress.borrow()._type, "type") };
+    let Component = derive_from_js_value(&memo_type, "type");
     update_function_component(work_in_progress.clone(), Component, render_lane)
 }
 
@@ -201,6 +471,32 @@ fn update_context_provider(
     work_in_progress.clone().borrow().child.clone()
 }
 
+fn update_context_consumer(
+    work_in_progress: Rc<RefCell<FiberNode>>,
+    render_lane: Lane,
+) -> Result<Option<Rc<RefCell<FiberNode>>>, JsValue> {
+    let context = { work_in_progress.borrow()._type.clone() };
+    let new_props = { work_in_progress.borrow().pending_props.clone() };
+    let render = derive_from_js_value(&new_props, "children");
+    if !render.is_function() {
+        error!(
+            "A context consumer was rendered with multiple children, or a child that isn't a \
+            function. A context consumer expects a single child that is a function."
+        );
+        return Ok(None);
+    }
+
+    prepare_to_read_context(work_in_progress.clone(), render_lane);
+    let new_value = read_context(Some(work_in_progress.clone()), context);
+    let new_children = render
+        .dyn_ref::<Function>()
+        .unwrap()
+        .call1(&JsValue::null(), &new_value)?;
+
+    reconcile_children(work_in_progress.clone(), Some(new_children));
+    Ok(work_in_progress.clone().borrow().child.clone())
+}
+
 fn update_function_component(
     work_in_progress: Rc<RefCell<FiberNode>>,
     Component: JsValue,
@@ -259,9 +555,17 @@ fn update_host_root(
             .borrow_mut()
             .shared
             .pending = None;
-        let ReturnOfProcessUpdateQueue { memoized_state, .. } =
-            process_update_queue(base_state, pending, render_lane.clone(), None);
+        let ReturnOfProcessUpdateQueue {
+            memoized_state,
+            callbacks,
+            ..
+        } = process_update_queue(base_state, pending, render_lane.clone(), None);
         work_in_progress.clone().borrow_mut().memoized_state = memoized_state.clone();
+        if !callbacks.is_empty() {
+            let update_queue = { work_in_progress.borrow().update_queue.clone().unwrap() };
+            update_queue.borrow_mut().callbacks.extend(callbacks);
+            work_in_progress.borrow_mut().flags |= Flags::Callback;
+        }
         let current = { work_in_progress.borrow().alternate.clone() };
         if current.is_some() {
             let current = current.unwrap();
@@ -1,12 +1,15 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::js_sys::{Array, Object, Reflect};
+use web_sys::js_sys::{Array, Object, Reflect, Symbol};
 
-use shared::{derive_from_js_value, log, type_of, REACT_ELEMENT_TYPE, REACT_FRAGMENT_TYPE};
+use shared::{
+    derive_from_js_value, error, is_dev, log, type_of, REACT_ELEMENT_TYPE, REACT_FRAGMENT_TYPE,
+    REACT_PORTAL_TYPE,
+};
 
 use crate::fiber::FiberNode;
 use crate::fiber_flags::Flags;
@@ -20,6 +23,11 @@ fn use_fiber(fiber: Rc<RefCell<FiberNode>>, pending_props: JsValue) -> Rc<RefCel
     clone
 }
 
+// The reused fiber takes the ref of the new element
+fn coerce_ref(fiber: Rc<RefCell<FiberNode>>, element: &JsValue) {
+    fiber.borrow_mut()._ref = derive_from_js_value(element, "ref");
+}
+
 fn place_single_child(
     fiber: Rc<RefCell<FiberNode>>,
     should_track_effects: bool,
@@ -104,15 +112,23 @@ fn reconcile_single_element(
                 panic!("Undefined $$typeof");
             }
 
-            if Object::is(
-                &current_cloned.borrow()._type,
-                &derive_from_js_value(&(*element).clone(), "type"),
-            ) {
+            let element_type = derive_from_js_value(&(*element).clone(), "type");
+            let is_fragment = element_type == REACT_FRAGMENT_TYPE;
+            // The type of fragment fiber is null, so compare the tag instead
+            if (is_fragment && current_cloned.borrow().tag == WorkTag::Fragment)
+                || (!is_fragment && Object::is(&current_cloned.borrow()._type, &element_type))
+            {
                 // type is the same, update props
+                let props = derive_from_js_value(&(*element).clone(), "props");
                 let existing = use_fiber(
                     current_cloned.clone(),
-                    derive_from_js_value(&(*element).clone(), "props"),
+                    if is_fragment {
+                        derive_from_js_value(&props, "children")
+                    } else {
+                        props
+                    },
                 );
+                coerce_ref(existing.clone(), element);
                 existing.clone().borrow_mut()._return = Some(return_fiber.clone());
                 delete_remaining_children(
                     return_fiber.clone(),
@@ -133,19 +149,76 @@ fn reconcile_single_element(
         }
     }
 
-    let mut fiber ;
+    let mut fiber;
     if derive_from_js_value(&element, "type") == REACT_FRAGMENT_TYPE {
         let props = derive_from_js_value(&element, "props");
         let children = derive_from_js_value(&props, "children");
-        fiber = FiberNode::create_fiber_from_fragment(children, key);
+        fiber = FiberNode::create_fiber_from_fragment(
+            children,
+            key,
+            return_fiber.borrow().mode.clone(),
+        );
     } else {
-        fiber = FiberNode::create_fiber_from_element(element);
+        fiber = FiberNode::create_fiber_from_element(element, return_fiber.borrow().mode.clone());
     }
-   
+
     fiber._return = Some(return_fiber.clone());
     Rc::new(RefCell::new(fiber))
 }
 
+fn is_same_portal(fiber: Rc<RefCell<FiberNode>>, portal: &JsValue) -> bool {
+    if fiber.borrow().tag != WorkTag::HostPortal {
+        return false;
+    }
+    match FiberNode::derive_portal_container_info(fiber) {
+        Some(container_info) => Object::is(
+            &container_info,
+            &derive_from_js_value(portal, "containerInfo"),
+        ),
+        None => false,
+    }
+}
+
+fn reconcile_single_portal(
+    return_fiber: Rc<RefCell<FiberNode>>,
+    current_first_child: Option<Rc<RefCell<FiberNode>>>,
+    portal: &JsValue,
+    should_track_effects: bool,
+) -> Rc<RefCell<FiberNode>> {
+    let key = derive_from_js_value(portal, "key");
+    let mut current = current_first_child;
+    while current.is_some() {
+        let current_rc = current.clone().unwrap();
+        if Object::is(&current_rc.borrow().key, &key) {
+            if is_same_portal(current_rc.clone(), portal) {
+                let existing =
+                    use_fiber(current_rc.clone(), derive_from_js_value(portal, "children"));
+                existing.borrow_mut()._return = Some(return_fiber.clone());
+                delete_remaining_children(
+                    return_fiber.clone(),
+                    current_rc.borrow().sibling.clone(),
+                    should_track_effects,
+                );
+                return existing;
+            }
+            delete_remaining_children(return_fiber.clone(), current.clone(), should_track_effects);
+            break;
+        } else {
+            delete_child(
+                return_fiber.clone(),
+                current_rc.clone(),
+                should_track_effects,
+            );
+        }
+        current = current_rc.borrow().sibling.clone();
+    }
+
+    let mut created =
+        FiberNode::create_fiber_from_portal(portal, return_fiber.borrow().mode.clone());
+    created._return = Some(return_fiber.clone());
+    Rc::new(RefCell::new(created))
+}
+
 fn create_props_with_content(content: JsValue) -> JsValue {
     let props = Object::new();
     Reflect::set(&props, &JsValue::from("content"), &content).expect("props panic");
@@ -180,12 +253,7 @@ fn reconcile_single_text_node(
         current = current_rc.borrow().sibling.clone();
     }
 
-    let mut created = FiberNode::new(
-        WorkTag::HostText,
-        props.clone(),
-        JsValue::null(),
-        JsValue::null(),
-    );
+    let mut created = FiberNode::create_fiber_from_text(props, return_fiber.borrow().mode.clone());
     created._return = Some(return_fiber.clone());
     Rc::new(RefCell::new(created))
 }
@@ -223,7 +291,9 @@ fn update_fragment(
     let fiber;
     if current.is_none() || current.clone().unwrap().borrow().tag != WorkTag::Fragment {
         fiber = Rc::new(RefCell::new(FiberNode::create_fiber_from_fragment(
-            elements, key.0,
+            elements,
+            key.0,
+            return_fiber.borrow().mode.clone(),
         )));
     } else {
         existing_children.remove(&key);
@@ -240,6 +310,7 @@ fn update_from_map(
     element: &JsValue,
     should_track_effects: bool,
 ) -> Option<Rc<RefCell<FiberNode>>> {
+    let mode = { return_fiber.borrow().mode.clone() };
     let key_to_use;
     if type_of(element, "string")
         || type_of(element, "null")
@@ -271,14 +342,12 @@ fn update_from_map(
         return if type_of(element, "null") {
             None
         } else {
-            Some(Rc::new(RefCell::new(FiberNode::new(
-                WorkTag::HostText,
+            Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_text(
                 props.clone(),
-                JsValue::null(),
-                JsValue::null(),
+                mode.clone(),
             ))))
         };
-    } else if element.is_array() {
+    } else if element.is_array() || is_iterable(element) {
         let before = match before {
             Some(before) => Some((*before).clone()),
             None => None,
@@ -286,7 +355,7 @@ fn update_from_map(
         return Some(update_fragment(
             return_fiber,
             before,
-            (*element).clone(),
+            Array::from(element).into(),
             Key(key_to_use.clone()),
             existing_children,
         ));
@@ -297,10 +366,11 @@ fn update_from_map(
                     Some(before) => Some((*before).clone()),
                     None => None,
                 };
+                let props = derive_from_js_value(element, "props");
                 return Some(update_fragment(
                     return_fiber,
                     before,
-                    (*element).clone(),
+                    derive_from_js_value(&props, "children"),
                     Key(key_to_use.clone()),
                     existing_children,
                 ));
@@ -313,19 +383,37 @@ fn update_from_map(
                     &before.borrow()._type,
                     &derive_from_js_value(&(*element).clone(), "type"),
                 ) {
+                    let existing =
+                        use_fiber(before.clone(), derive_from_js_value(element, "props"));
+                    coerce_ref(existing.clone(), element);
+                    return Some(existing);
+                }
+                // The key matches but the type changes, the old fiber can't be reused
+                delete_child(return_fiber, before, should_track_effects);
+            }
+
+            return Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_element(
+                element,
+                mode.clone(),
+            ))));
+        }
+
+        if derive_from_js_value(&(*element).clone(), "$$typeof") == REACT_PORTAL_TYPE {
+            if before.is_some() {
+                let before = (*before.clone().unwrap()).clone();
+                existing_children.remove(&Key(key_to_use.clone()));
+                if is_same_portal(before.clone(), element) {
                     return Some(use_fiber(
                         before.clone(),
-                        derive_from_js_value(element, "props"),
+                        derive_from_js_value(element, "children"),
                     ));
                 }
-
-                // else {
-                //     delete_child(return_fiber, before, should_track_effects);
-                // }
+                delete_child(return_fiber, before, should_track_effects);
             }
 
-            return Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_element(
+            return Some(Rc::new(RefCell::new(FiberNode::create_fiber_from_portal(
                 element,
+                mode.clone(),
             ))));
         }
     }
@@ -333,6 +421,62 @@ fn update_from_map(
     None
 }
 
+// Children other than arrays that implement the iterator protocol, e.g. Map.values() or a generator
+fn is_iterable(value: &JsValue) -> bool {
+    type_of(value, "object")
+        && !value.is_null()
+        && Reflect::get(value, &Symbol::iterator())
+            .map(|iterator_fn| iterator_fn.is_function())
+            .unwrap_or(false)
+}
+
+// Warn once about the elements of a list that have no key, they can only be matched by index
+fn warn_on_missing_key(children: &Array, return_fiber: Rc<RefCell<FiberNode>>) {
+    let has_missing_key = children.iter().any(|child| {
+        type_of(&child, "object")
+            && !child.is_null()
+            && derive_from_js_value(&child, "$$typeof") == REACT_ELEMENT_TYPE
+            && derive_from_js_value(&child, "key").is_null()
+    });
+    if has_missing_key {
+        error!(
+            "Each child in a list should have a unique \"key\" prop.\n    in {}",
+            return_fiber.borrow().get_component_name()
+        );
+    }
+}
+
+// Warn about the children that have the same key as one of their previous siblings
+fn warn_on_duplicate_key(
+    child: &JsValue,
+    known_keys: &mut HashSet<String>,
+    return_fiber: Rc<RefCell<FiberNode>>,
+) {
+    if !type_of(child, "object") || child.is_null() {
+        return;
+    }
+    let _typeof = derive_from_js_value(child, "$$typeof");
+    if _typeof != REACT_ELEMENT_TYPE && _typeof != REACT_PORTAL_TYPE {
+        return;
+    }
+    let key = derive_from_js_value(child, "key");
+    let key = match key.as_string() {
+        Some(key) => key,
+        None => return,
+    };
+    if known_keys.insert(key.clone()) {
+        return;
+    }
+    error!(
+        "Encountered two children with the same key, `{}`. Keys should be unique so that \
+        components maintain their identity across updates. Non-unique keys may cause children to \
+        be duplicated and/or omitted — the behavior is unsupported and could change in a future \
+        version.\n    in {}",
+        key,
+        return_fiber.borrow().get_component_name()
+    );
+}
+
 fn reconcile_children_array(
     return_fiber: Rc<RefCell<FiberNode>>,
     current_first_child: Option<Rc<RefCell<FiberNode>>>,
@@ -359,6 +503,12 @@ fn reconcile_children_array(
     }
     // log!("existing_children {:?}", existing_children.keys());
     let length = new_child.length();
+    if is_dev() {
+        let mut known_keys = HashSet::new();
+        for i in 0..length {
+            warn_on_duplicate_key(&new_child.get(i), &mut known_keys, return_fiber.clone());
+        }
+    }
     for i in 0..length {
         let after = new_child.get(i);
         // log!("after {:?}", after);
@@ -410,11 +560,7 @@ fn reconcile_children_array(
     for (_, fiber) in existing_children {
         delete_child(return_fiber.clone(), fiber, should_track_effects);
     }
-    log!(
-        "first_new_fiber {:?} {:?}",
-        first_new_fiber,
-        first_new_fiber.clone().unwrap().borrow().sibling
-    );
+    log!("first_new_fiber {:?}", first_new_fiber);
     first_new_fiber
 }
 
@@ -444,6 +590,18 @@ fn _reconcile_child_fibers(
                 new_child.dyn_ref::<Array>().unwrap(),
                 should_track_effects,
             );
+        } else if is_iterable(new_child) {
+            // An iterator can only be consumed once, its children are collected up front
+            let children = Array::from(new_child);
+            if is_dev() {
+                warn_on_missing_key(&children, return_fiber.clone());
+            }
+            return reconcile_children_array(
+                return_fiber,
+                current_first_child,
+                &children,
+                should_track_effects,
+            );
         } else if new_child.is_object() {
             if let Some(_typeof) = derive_from_js_value(&new_child, "$$typeof").as_string() {
                 if _typeof == REACT_ELEMENT_TYPE {
@@ -456,6 +614,16 @@ fn _reconcile_child_fibers(
                         ),
                         should_track_effects,
                     ));
+                } else if _typeof == REACT_PORTAL_TYPE {
+                    return Some(place_single_child(
+                        reconcile_single_portal(
+                            return_fiber,
+                            current_first_child,
+                            new_child,
+                            should_track_effects,
+                        ),
+                        should_track_effects,
+                    ));
                 }
             }
         }
@@ -5,20 +5,22 @@ use std::rc::Rc;
 use wasm_bindgen::{JsCast, JsValue};
 use web_sys::js_sys::{Function, Reflect};
 
-use shared::{derive_from_js_value, log, type_of};
-use web_sys::Node;
+use shared::{derive_from_js_value, error, is_dev, log, to_string, type_of};
 
 use crate::fiber::{FiberNode, FiberRootNode, StateNode};
 use crate::fiber_flags::{get_mutation_mask, get_passive_mask, Flags};
 use crate::fiber_hooks::Effect;
+use crate::work_loop::mark_commit_time_of_fallback;
 use crate::work_tags::WorkTag;
-use crate::work_tags::WorkTag::{FunctionComponent, HostComponent, HostRoot, HostText};
+use crate::work_tags::WorkTag::{
+    FunctionComponent, HostComponent, HostPortal, HostRoot, HostText, OffscreenComponent,
+    SimpleMemoComponent,
+};
 use crate::HOST_CONFIG;
 
 static mut NEXT_EFFECT: Option<Rc<RefCell<FiberNode>>> = None;
 
 enum Phrase {
-    Mutation,
     Layout,
 }
 
@@ -28,7 +30,8 @@ fn commit_passive_effect(
     _type: &str,
 ) {
     let finished_work_b = finished_work.borrow();
-    if finished_work_b.tag != WorkTag::FunctionComponent
+    if (finished_work_b.tag != WorkTag::FunctionComponent
+        && finished_work_b.tag != WorkTag::SimpleMemoComponent)
         || (_type == "update"
             && (finished_work_b.flags.clone() & Flags::PassiveEffect == Flags::NoFlags))
     {
@@ -107,11 +110,43 @@ pub fn commit_hook_effect_list_mount(flags: Flags, last_effect: Rc<RefCell<Effec
         let create = { effect.borrow().create.clone() };
         if create.is_function() {
             let destroy = create.call0(&JsValue::null()).unwrap();
+            if is_dev() {
+                validate_effect_destroy(&destroy);
+            }
             effect.borrow_mut().destroy = destroy;
         }
     });
 }
 
+// The create of an effect returns nothing or a cleanup function, an async create returns a Promise
+fn validate_effect_destroy(destroy: &JsValue) {
+    if destroy.is_undefined() || destroy.is_function() {
+        return;
+    }
+    if destroy.is_null() {
+        error!(
+            "useEffect must not return anything besides a function, which is used for clean-up. \
+            You returned null. If your effect does not require clean up, return undefined (or \
+            nothing)."
+        );
+    } else if destroy.is_object() && derive_from_js_value(destroy, "then").is_function() {
+        error!(
+            "useEffect must not return anything besides a function, which is used for clean-up.\n\n\
+            It looks like you wrote useEffect(async () => ...) or returned a Promise. Instead, \
+            write the async function inside your effect and call it immediately:\n\n\
+            useEffect(() => {{\n  async function fetchData() {{\n    // You can await here\n    \
+            const response = await MyAPI.getData(someId);\n    // ...\n  }}\n  fetchData();\n\
+            }}, [someId]); // Or [] if effect doesn't need props or state"
+        );
+    } else {
+        error!(
+            "useEffect must not return anything besides a function, which is used for clean-up. \
+            You returned: {}",
+            to_string(destroy)
+        );
+    }
+}
+
 pub fn commit_effects(
     phrase: Phrase,
     mask: Flags,
@@ -180,11 +215,33 @@ pub fn commit_mutation_effects(
     finished_work: Rc<RefCell<FiberNode>>,
     root: Rc<RefCell<FiberRootNode>>,
 ) {
-    commit_effects(
-        Phrase::Mutation,
-        get_mutation_mask() | get_passive_mask(),
-        commit_mutation_effects_on_fiber,
-    )(finished_work, root)
+    commit_mutation_effects_on_fiber(finished_work, root)
+}
+
+// Deletions of a fiber are committed before its children, so the children never
+// reference a sibling that's about to be removed, then the fiber itself is placed
+// after its subtree has been completed
+fn recursively_traverse_mutation_effects(
+    parent_fiber: Rc<RefCell<FiberNode>>,
+    root: Rc<RefCell<FiberRootNode>>,
+) {
+    let flags = { parent_fiber.borrow().flags.clone() };
+    if flags.contains(Flags::ChildDeletion) {
+        let deletions = { parent_fiber.borrow().deletions.clone() };
+        for child_to_delete in deletions {
+            commit_deletion(child_to_delete, root.clone());
+        }
+        parent_fiber.borrow_mut().flags -= Flags::ChildDeletion;
+    }
+
+    let subtree_flags = { parent_fiber.borrow().subtree_flags.clone() };
+    if subtree_flags.intersects(get_mutation_mask() | get_passive_mask()) {
+        let mut child = { parent_fiber.borrow().child.clone() };
+        while let Some(fiber) = child {
+            commit_mutation_effects_on_fiber(fiber.clone(), root.clone());
+            child = fiber.borrow().sibling.clone();
+        }
+    }
 }
 
 fn commit_layout_effects_on_fiber(
@@ -193,41 +250,69 @@ fn commit_layout_effects_on_fiber(
 ) {
     let flags = finished_work.borrow().flags.clone();
     let tag = finished_work.borrow().tag.clone();
-    if flags & Flags::Ref != Flags::NoFlags && tag == HostComponent {
+    if flags.clone() & Flags::Ref != Flags::NoFlags && tag == HostComponent {
         safely_attach_ref(finished_work.clone());
         finished_work.borrow_mut().flags -= Flags::Ref;
     }
+
+    if flags.contains(Flags::Update) && tag == HostComponent {
+        let is_mount = { finished_work.borrow().alternate.is_none() };
+        if is_mount {
+            let instance = FiberNode::derive_state_node(finished_work.clone()).unwrap();
+            let _type = { finished_work.borrow()._type.as_string().unwrap() };
+            let props = { finished_work.borrow().memoized_props.clone() };
+            unsafe {
+                HOST_CONFIG
+                    .as_ref()
+                    .unwrap()
+                    .commit_mount(instance, _type, &props)
+            }
+            finished_work.borrow_mut().flags -= Flags::Update;
+        }
+    }
+
+    if flags.contains(Flags::Callback) && tag == HostRoot {
+        commit_update_queue_callbacks(finished_work.clone());
+        finished_work.borrow_mut().flags -= Flags::Callback;
+    }
+}
+
+fn commit_update_queue_callbacks(finished_work: Rc<RefCell<FiberNode>>) {
+    let update_queue = { finished_work.borrow().update_queue.clone() };
+    if let Some(update_queue) = update_queue {
+        let callbacks = std::mem::take(&mut update_queue.borrow_mut().callbacks);
+        for callback in callbacks {
+            // The other callbacks are still called when one of them throws
+            if let Err(e) = callback.call0(&JsValue::null()) {
+                error!(
+                    "An error was thrown by a render callback: {}",
+                    to_string(&e)
+                );
+            }
+        }
+    }
 }
 
 fn commit_mutation_effects_on_fiber(
     finished_work: Rc<RefCell<FiberNode>>,
     root: Rc<RefCell<FiberRootNode>>,
 ) {
+    recursively_traverse_mutation_effects(finished_work.clone(), root.clone());
+
     let flags = finished_work.borrow().flags.clone();
     if flags.contains(Flags::Placement) {
         commit_placement(finished_work.clone());
         finished_work.borrow_mut().flags -= Flags::Placement;
     }
 
-    if flags.contains(Flags::ChildDeletion) {
-        {
-            let deletions = &finished_work.borrow().deletions;
-            if !deletions.is_empty() {
-                for child_to_delete in deletions {
-                    commit_deletion(child_to_delete.clone(), root.clone());
-                }
-            }
-        }
-
-        finished_work.borrow_mut().flags -= Flags::ChildDeletion;
-    }
-
     // log!(
     //     "finished_work {:?} {:?}",
     //     finished_work,
     //     finished_work.borrow().alternate
     // );
-    if flags.contains(Flags::Update) {
+    // The Update of a newly mounted host component is for commit_mount in the layout phase
+    let is_mount = { finished_work.borrow().alternate.is_none() };
+    if flags.contains(Flags::Update) && !is_mount {
         // commit_update(finished_work.clone());
         unsafe {
             HOST_CONFIG
@@ -243,8 +328,87 @@ fn commit_mutation_effects_on_fiber(
         finished_work.borrow_mut().flags -= Flags::PassiveEffect;
     }
 
-    if flags & Flags::Ref != Flags::NoFlags && finished_work.borrow().tag.clone() == HostComponent {
-        safely_detach_ref(finished_work);
+    if flags.clone() & Flags::Ref != Flags::NoFlags
+        && finished_work.borrow().tag.clone() == HostComponent
+    {
+        // Detach the old ref, the new one is attached in the layout phase
+        let current = { finished_work.borrow().alternate.clone() };
+        if let Some(current) = current {
+            safely_detach_ref(current);
+        }
+    }
+
+    if flags.contains(Flags::Visibility) && finished_work.borrow().tag == OffscreenComponent {
+        let is_hidden =
+            derive_from_js_value(&finished_work.borrow().pending_props, "mode") == "hidden";
+        hide_or_unhide_all_children(finished_work.clone(), is_hidden);
+        if is_hidden {
+            mark_commit_time_of_fallback();
+        }
+        finished_work.borrow_mut().flags -= Flags::Visibility;
+    }
+}
+
+fn hide_or_unhide_all_children(finished_work: Rc<RefCell<FiberNode>>, is_hidden: bool) {
+    find_host_subtree_root(finished_work, |host_root| {
+        let instance = FiberNode::derive_state_node(host_root.clone()).unwrap();
+        let host_config = unsafe { HOST_CONFIG.as_ref().unwrap() };
+        let tag = { host_root.borrow().tag.clone() };
+        if tag == HostComponent {
+            if is_hidden {
+                host_config.hide_instance(instance);
+            } else {
+                host_config.unhide_instance(instance, &host_root.borrow().memoized_props);
+            }
+        } else if tag == HostText {
+            if is_hidden {
+                host_config.hide_text_instance(instance);
+            } else {
+                let content = derive_from_js_value(&host_root.borrow().memoized_props, "content");
+                host_config.unhide_text_instance(instance, &content);
+            }
+        }
+    });
+}
+
+// Call `callback` with the top-level host nodes of the subtree, nested hidden offscreen
+// subtrees are skipped since their visibility is controlled by themselves
+fn find_host_subtree_root<F>(finished_work: Rc<RefCell<FiberNode>>, callback: F)
+where
+    F: Fn(Rc<RefCell<FiberNode>>),
+{
+    let mut node = finished_work.clone();
+    loop {
+        let tag = { node.borrow().tag.clone() };
+        if tag == HostComponent || tag == HostText {
+            callback(node.clone());
+        } else if tag == OffscreenComponent
+            && derive_from_js_value(&node.borrow().pending_props, "mode") == "hidden"
+            && !Rc::ptr_eq(&node, &finished_work)
+        {
+            // nothing
+        } else if node.borrow().child.is_some() {
+            let child = { node.borrow().child.clone().unwrap() };
+            child.borrow_mut()._return = Some(node.clone());
+            node = child;
+            continue;
+        }
+
+        if Rc::ptr_eq(&node, &finished_work) {
+            return;
+        }
+
+        while node.borrow().sibling.is_none() {
+            let _return = { node.borrow()._return.clone() };
+            if _return.is_none() || Rc::ptr_eq(_return.as_ref().unwrap(), &finished_work) {
+                return;
+            }
+            node = _return.unwrap();
+        }
+
+        let sibling = { node.borrow().sibling.clone().unwrap() };
+        sibling.borrow_mut()._return = node.borrow()._return.clone();
+        node = sibling;
     }
 }
 
@@ -266,11 +430,13 @@ fn safely_attach_ref(fiber: Rc<RefCell<FiberNode>>) {
     if !_ref.is_null() {
         let instance = match fiber.borrow().state_node.clone() {
             Some(s) => match &*s {
-                StateNode::Element(element) => {
-                    let node = (*element).downcast_ref::<Node>().unwrap();
-                    Some(node.clone())
-                }
-                StateNode::FiberRootNode(_) => None,
+                StateNode::Element(element) => Some(unsafe {
+                    HOST_CONFIG
+                        .as_ref()
+                        .unwrap()
+                        .get_public_instance(element.clone())
+                }),
+                StateNode::FiberRootNode(_) | StateNode::Portal(..) => None,
             },
             None => None,
         };
@@ -312,26 +478,51 @@ fn safely_attach_ref(fiber: Rc<RefCell<FiberNode>>) {
 
 fn commit_deletion(child_to_delete: Rc<RefCell<FiberNode>>, root: Rc<RefCell<FiberRootNode>>) {
     let first_host_fiber: Rc<RefCell<Option<Rc<RefCell<FiberNode>>>>> = Rc::new(RefCell::new(None));
+    let portals: Rc<RefCell<Vec<Rc<RefCell<FiberNode>>>>> = Rc::new(RefCell::new(vec![]));
+    let deleted_instances: RefCell<Vec<Rc<dyn Any>>> = RefCell::new(vec![]);
     commit_nested_unmounts(child_to_delete.clone(), |unmount_fiber| {
         let cloned = first_host_fiber.clone();
         match unmount_fiber.borrow().tag {
-            FunctionComponent => {
+            FunctionComponent | SimpleMemoComponent => {
                 commit_passive_effect(unmount_fiber.clone(), root.clone(), "unmount");
             }
-            HostComponent => {
-                if cloned.borrow().is_none() {
-                    *cloned.borrow_mut() = Some(unmount_fiber.clone());
+            HostComponent | HostText => {
+                if let Some(instance) = FiberNode::derive_state_node(unmount_fiber.clone()) {
+                    deleted_instances.borrow_mut().push(instance);
                 }
-            }
-            HostText => {
-                if cloned.borrow().is_none() {
+                // The host nodes in a portal are removed from the portal container below
+                if cloned.borrow().is_none()
+                    && !is_inside_portal(unmount_fiber.clone(), child_to_delete.clone())
+                {
                     *cloned.borrow_mut() = Some(unmount_fiber.clone());
                 }
             }
-            _ => todo!(),
+            HostPortal => {
+                portals.borrow_mut().push(unmount_fiber.clone());
+            }
+            // TODO: there is no hydration yet, so a SuspenseComponent is never dehydrated. Once
+            // it can be, its comment markers and dehydrated nodes have to be removed here.
+            _ => {}
         };
     });
 
+    for portal in portals.borrow().iter() {
+        let container = FiberNode::derive_state_node(portal.clone()).unwrap();
+        let mut child = { portal.borrow().child.clone() };
+        while let Some(fiber) = child {
+            find_host_subtree_root(fiber.clone(), |host_fiber| {
+                let instance = FiberNode::derive_state_node(host_fiber).unwrap();
+                unsafe {
+                    HOST_CONFIG
+                        .as_ref()
+                        .unwrap()
+                        .remove_child(instance, container.clone())
+                }
+            });
+            child = fiber.borrow().sibling.clone();
+        }
+    }
+
     let first_host_fiber = first_host_fiber.clone();
     if first_host_fiber.borrow().is_some() {
         let host_parent_state_node =
@@ -346,10 +537,33 @@ fn commit_deletion(child_to_delete: Rc<RefCell<FiberNode>>, root: Rc<RefCell<Fib
         }
     }
 
+    for instance in deleted_instances.take() {
+        unsafe {
+            HOST_CONFIG
+                .as_ref()
+                .unwrap()
+                .detach_deleted_instance(instance)
+        }
+    }
+
     child_to_delete.clone().borrow_mut()._return = None;
     child_to_delete.clone().borrow_mut().child = None;
 }
 
+fn is_inside_portal(fiber: Rc<RefCell<FiberNode>>, root: Rc<RefCell<FiberNode>>) -> bool {
+    let mut node = Some(fiber);
+    while let Some(fiber) = node {
+        if fiber.borrow().tag == HostPortal {
+            return true;
+        }
+        if Rc::ptr_eq(&fiber, &root) {
+            return false;
+        }
+        node = fiber.borrow()._return.clone();
+    }
+    false
+}
+
 fn commit_nested_unmounts<F>(root: Rc<RefCell<FiberNode>>, on_commit_unmount: F)
 where
     F: Fn(Rc<RefCell<FiberNode>>),
@@ -418,6 +632,7 @@ fn get_element_from_state_node(state_node: Rc<StateNode>) -> Rc<dyn Any> {
     match &*state_node {
         StateNode::FiberRootNode(root) => root.clone().borrow().container.clone(),
         StateNode::Element(ele) => ele.clone(),
+        StateNode::Portal(_, container) => container.clone(),
     }
 }
 
@@ -452,6 +667,11 @@ fn insert_or_append_placement_node_into_container(
         return;
     }
 
+    if tag == WorkTag::HostPortal {
+        // The children of a portal are placed into its own container
+        return;
+    }
+
     let child = fiber.borrow().child.clone();
     if child.is_some() {
         insert_or_append_placement_node_into_container(
@@ -476,7 +696,10 @@ fn get_host_parent(fiber: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode
     while parent.is_some() {
         let p = parent.clone().unwrap();
         let parent_tag = p.borrow().tag.clone();
-        if parent_tag == WorkTag::HostComponent || parent_tag == WorkTag::HostRoot {
+        if parent_tag == WorkTag::HostComponent
+            || parent_tag == WorkTag::HostRoot
+            || parent_tag == WorkTag::HostPortal
+        {
             return Some(p);
         }
         parent = p.borrow()._return.clone();
@@ -498,7 +721,7 @@ fn get_host_sibling(fiber: Rc<RefCell<FiberNode>>) -> Option<Rc<dyn Any>> {
             let node_rc = node.clone().unwrap();
             let parent = node_rc.borrow()._return.clone();
             let tag = parent.clone().unwrap().borrow().tag.clone();
-            if parent.is_none() || tag == HostComponent || tag == HostRoot {
+            if parent.is_none() || tag == HostComponent || tag == HostRoot || tag == HostPortal {
                 return None;
             }
             node = parent.clone();
@@ -522,7 +745,8 @@ fn get_host_sibling(fiber: Rc<RefCell<FiberNode>>) -> Option<Rc<dyn Any>> {
             if node_rc.borrow().flags.contains(Flags::Placement) {
                 continue 'find_sibling;
             }
-            if node_rc.borrow().child.is_none() {
+            // The children of a portal are in another container
+            if node_rc.borrow().child.is_none() || node_rc.borrow().tag == HostPortal {
                 continue 'find_sibling;
             } else {
                 node_rc
@@ -11,6 +11,7 @@ use crate::fiber::{FiberNode, StateNode};
 use crate::fiber_context::pop_provider;
 use crate::fiber_flags::Flags;
 use crate::fiber_lanes::{merge_lanes, Lane};
+use crate::suspense_context::pop_suspense_handler;
 use crate::work_tags::WorkTag;
 use crate::HostConfig;
 
@@ -38,6 +39,8 @@ impl CompleteWork {
                     parent.clone(),
                     FiberNode::derive_state_node(node.clone().unwrap()).unwrap(),
                 )
+            } else if n.borrow().tag == WorkTag::HostPortal {
+                // The children of a portal are placed into its container in the commit phase
             } else if n.borrow().child.is_some() {
                 let n = node_unwrap.clone();
                 {
@@ -125,7 +128,8 @@ impl CompleteWork {
             }
         }
         complete_work.clone().borrow_mut().subtree_flags |= subtree_flags.clone();
-        complete_work.clone().borrow_mut().child_lanes |= new_child_lanes.clone();
+        // Assigned rather than merged, a deleted child doesn't leave its lanes behind
+        complete_work.clone().borrow_mut().child_lanes = new_child_lanes.clone();
     }
 
     fn mark_update(fiber: Rc<RefCell<FiberNode>>) {
@@ -143,9 +147,12 @@ impl CompleteWork {
         match tag {
             WorkTag::HostComponent => {
                 if current.is_some() && work_in_progress_cloned.borrow().state_node.is_some() {
-                    // todo: compare props to decide if need to update
-                    CompleteWork::mark_update(work_in_progress.clone());
+                    // The instance of the current fiber is kept, the commit applies the difference
+                    // of the props to it. The same props object means nothing changed.
                     let current = current.unwrap();
+                    if !Object::is(&current.borrow().memoized_props, &new_props) {
+                        CompleteWork::mark_update(work_in_progress.clone());
+                    }
                     if !Object::is(
                         &current.borrow()._ref,
                         &work_in_progress_cloned.borrow()._ref,
@@ -161,11 +168,20 @@ impl CompleteWork {
                             .as_ref()
                             .as_string()
                             .unwrap(),
-                        Rc::new(new_props),
+                        Rc::new(new_props.clone()),
                     );
                     self.append_all_children(instance.clone(), work_in_progress.clone());
                     work_in_progress.clone().borrow_mut().state_node =
                         Some(Rc::new(StateNode::Element(instance.clone())));
+                    let _type = { work_in_progress.borrow()._type.as_string().unwrap() };
+                    if self.host_config.finalize_initial_children(
+                        instance.clone(),
+                        _type,
+                        &new_props,
+                    ) {
+                        // commit_mount is called in the layout phase
+                        CompleteWork::mark_update(work_in_progress.clone());
+                    }
                     if !work_in_progress.borrow()._ref.is_null() {
                         mark_ref(work_in_progress.clone());
                     }
@@ -212,6 +228,33 @@ impl CompleteWork {
                 self.bubble_properties(work_in_progress.clone());
                 None
             }
+            WorkTag::ContextConsumer => {
+                self.bubble_properties(work_in_progress.clone());
+                None
+            }
+            WorkTag::SuspenseComponent => {
+                pop_suspense_handler();
+                let offscreen_fiber = { work_in_progress.borrow().child.clone().unwrap() };
+                let is_hidden =
+                    derive_from_js_value(&offscreen_fiber.borrow().pending_props, "mode")
+                        == "hidden";
+                let current_offscreen_fiber = { offscreen_fiber.borrow().alternate.clone() };
+                if current_offscreen_fiber.is_some() {
+                    let was_hidden = derive_from_js_value(
+                        &current_offscreen_fiber.unwrap().borrow().pending_props,
+                        "mode",
+                    ) == "hidden";
+                    if is_hidden != was_hidden {
+                        offscreen_fiber.borrow_mut().flags |= Flags::Visibility;
+                        self.bubble_properties(offscreen_fiber.clone());
+                    }
+                } else if is_hidden {
+                    offscreen_fiber.borrow_mut().flags |= Flags::Visibility;
+                    self.bubble_properties(offscreen_fiber.clone());
+                }
+                self.bubble_properties(work_in_progress.clone());
+                None
+            }
             _ => {
                 self.bubble_properties(work_in_progress.clone());
                 None
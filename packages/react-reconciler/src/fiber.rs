@@ -7,21 +7,32 @@ use std::rc::Rc;
 
 use scheduler::Task;
 use wasm_bindgen::JsValue;
-use web_sys::js_sys::Reflect;
+use web_sys::js_sys::{Map, Object, Reflect, WeakMap};
 
-use shared::{derive_from_js_value, log, type_of, REACT_MEMO_TYPE, REACT_PROVIDER_TYPE};
+use shared::{
+    derive_from_js_value, get_component_name_from_type, log, type_of, REACT_CONTEXT_TYPE,
+    REACT_MEMO_TYPE, REACT_PROVIDER_TYPE, REACT_STRICT_MODE_TYPE, REACT_SUSPENSE_TYPE,
+};
 
 use crate::fiber_context::ContextItem;
 use crate::fiber_flags::Flags;
 use crate::fiber_hooks::{Effect, Hook};
-use crate::fiber_lanes::{get_highest_priority, merge_lanes, Lane};
+use crate::fiber_lanes::{
+    clear_expiration_times, get_entangled_lanes, get_highest_priority, include_some_lanes,
+    is_subset_of_lanes, lane_to_index, mark_starved_lanes_as_expired, merge_lanes, Lane,
+    NO_TIMESTAMP, TOTAL_LANES,
+};
+use crate::type_of_mode::TypeOfMode;
 use crate::update_queue::{Update, UpdateQueue};
 use crate::work_tags::WorkTag;
+use crate::HOST_CONFIG;
 
 #[derive(Debug)]
 pub enum StateNode {
     FiberRootNode(Rc<RefCell<FiberRootNode>>),
     Element(Rc<dyn Any>),
+    // The container passed to createPortal and the host instance children are placed into
+    Portal(JsValue, Rc<dyn Any>),
 }
 
 #[derive(Debug, Clone)]
@@ -73,6 +84,7 @@ pub struct FiberNode {
     pub memoized_state: Option<MemoizedState>,
     pub deletions: Vec<Rc<RefCell<FiberNode>>>,
     pub dependencies: Option<Rc<RefCell<FiberDependencies>>>,
+    pub mode: TypeOfMode,
 }
 
 impl Debug for FiberNode {
@@ -101,8 +113,8 @@ impl Debug for FiberNode {
             _ => {
                 write!(
                     f,
-                    "{:?}(tag:{:?},key:{:?}flags:{:?},subtreeFlags:{:?},lanes:{:?},childLanes:{:?})",
-                    self._type.as_ref(),
+                    "{}(tag:{:?},key:{:?}flags:{:?},subtreeFlags:{:?},lanes:{:?},childLanes:{:?})",
+                    self.get_component_name(),
                     self.tag,
                     self.key,
                     self.flags,
@@ -117,6 +129,17 @@ impl Debug for FiberNode {
 }
 
 impl FiberNode {
+    pub fn get_component_name(&self) -> String {
+        match self.tag {
+            WorkTag::HostRoot => "Root".to_string(),
+            WorkTag::HostText => "Text".to_string(),
+            WorkTag::HostPortal => "Portal".to_string(),
+            WorkTag::Fragment => "Fragment".to_string(),
+            WorkTag::OffscreenComponent => "Offscreen".to_string(),
+            _ => get_component_name_from_type(&self._type).unwrap_or("Unknown".to_string()),
+        }
+    }
+
     pub fn new(tag: WorkTag, pending_props: JsValue, key: JsValue, _ref: JsValue) -> Self {
         Self {
             index: 0,
@@ -139,26 +162,58 @@ impl FiberNode {
             child_lanes: Lane::NoLane,
             _ref,
             dependencies: None,
+            mode: TypeOfMode::NoMode,
         }
     }
 
-    pub fn create_fiber_from_fragment(elements: JsValue, key: JsValue) -> FiberNode {
-        FiberNode::new(WorkTag::Fragment, elements, key, JsValue::null())
+    pub fn create_fiber_from_fragment(
+        elements: JsValue,
+        key: JsValue,
+        mode: TypeOfMode,
+    ) -> FiberNode {
+        let mut fiber = FiberNode::new(WorkTag::Fragment, elements, key, JsValue::null());
+        fiber.mode = mode;
+        fiber
+    }
+
+    pub fn create_fiber_from_text(props: JsValue, mode: TypeOfMode) -> FiberNode {
+        let mut fiber = FiberNode::new(WorkTag::HostText, props, JsValue::null(), JsValue::null());
+        fiber.mode = mode;
+        fiber
     }
 
-    pub fn create_fiber_from_element(ele: &JsValue) -> Self {
+    pub fn create_fiber_from_element(ele: &JsValue, mode: TypeOfMode) -> Self {
         let _type = derive_from_js_value(ele, "type");
         let key = derive_from_js_value(ele, "key");
         let props = derive_from_js_value(ele, "props");
         let _ref = derive_from_js_value(ele, "ref");
+        FiberNode::create_fiber_from_type_and_props(_type, key, props, _ref, mode)
+    }
 
+    pub fn create_fiber_from_type_and_props(
+        _type: JsValue,
+        key: JsValue,
+        props: JsValue,
+        _ref: JsValue,
+        mode: TypeOfMode,
+    ) -> Self {
+        let mut mode = mode;
+        // TODO: class components (prototype.isReactComponent) are not supported yet, there is no
+        // ClassComponent tag and so no class update path for getDerivedStateFromProps
         let mut fiber_tag = WorkTag::FunctionComponent;
-        if _type.is_string() {
+        if _type == REACT_SUSPENSE_TYPE {
+            fiber_tag = WorkTag::SuspenseComponent
+        } else if _type == REACT_STRICT_MODE_TYPE {
+            fiber_tag = WorkTag::Mode;
+            mode |= TypeOfMode::StrictLegacyMode | TypeOfMode::StrictEffectsMode;
+        } else if _type.is_string() {
             fiber_tag = WorkTag::HostComponent
         } else if type_of(&_type, "object") {
             let _typeof = derive_from_js_value(&_type, "$$typeof");
             if _typeof == REACT_PROVIDER_TYPE {
                 fiber_tag = WorkTag::ContextProvider;
+            } else if _typeof == REACT_CONTEXT_TYPE {
+                fiber_tag = WorkTag::ContextConsumer;
             } else if _typeof == REACT_MEMO_TYPE {
                 fiber_tag = WorkTag::MemoComponent;
             } else {
@@ -170,6 +225,48 @@ impl FiberNode {
 
         let mut fiber = FiberNode::new(fiber_tag, props, key, _ref);
         fiber._type = _type;
+        fiber.mode = mode;
+        fiber
+    }
+
+    // A plain function without defaultProps, a memo around it can render it in its own fiber
+    pub fn is_simple_function_component(_type: &JsValue) -> bool {
+        type_of(_type, "function") && derive_from_js_value(_type, "defaultProps").is_undefined()
+    }
+
+    pub fn create_fiber_from_portal(portal: &JsValue, mode: TypeOfMode) -> FiberNode {
+        let children = derive_from_js_value(portal, "children");
+        let key = derive_from_js_value(portal, "key");
+        let container_info = derive_from_js_value(portal, "containerInfo");
+        let container = unsafe {
+            HOST_CONFIG
+                .as_ref()
+                .unwrap()
+                .prepare_portal_mount(&container_info)
+        };
+        let mut fiber = FiberNode::new(WorkTag::HostPortal, children, key, JsValue::null());
+        fiber.mode = mode;
+        fiber.state_node = Some(Rc::new(StateNode::Portal(container_info, container)));
+        fiber
+    }
+
+    // The container passed to createPortal, only for HostPortal
+    pub fn derive_portal_container_info(fiber: Rc<RefCell<FiberNode>>) -> Option<JsValue> {
+        let state_node = { fiber.borrow().state_node.clone() }?;
+        match &*state_node {
+            StateNode::Portal(container_info, _) => Some(container_info.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn create_fiber_from_offscreen(pending_props: JsValue, mode: TypeOfMode) -> FiberNode {
+        let mut fiber = FiberNode::new(
+            WorkTag::OffscreenComponent,
+            pending_props,
+            JsValue::null(),
+            JsValue::null(),
+        );
+        fiber.mode = mode;
         fiber
     }
 
@@ -201,10 +298,10 @@ impl FiberNode {
                 let mut wip =
                     FiberNode::new(c.tag.clone(), pending_props, c.key.clone(), c._ref.clone());
                 wip._type = c._type.clone();
+                wip.mode = c.mode.clone();
                 wip.state_node = c.state_node.clone();
 
                 wip.update_queue = c.update_queue.clone();
-                wip.flags = c.flags.clone();
                 wip.child = c.child.clone();
                 wip.lanes = c.lanes.clone();
                 wip.child_lanes = c.child_lanes.clone();
@@ -232,13 +329,14 @@ impl FiberNode {
                 let mut wip = wip_cloned.borrow_mut();
                 let c = c_rc.borrow();
                 wip.pending_props = pending_props;
+                // The alternate still carries the effects of the render it was last used in,
+                // which were either committed already or thrown away with an interrupted render
                 wip.flags = Flags::NoFlags;
                 wip.subtree_flags = Flags::NoFlags;
                 wip.deletions = vec![];
                 wip._type = c._type.clone();
 
                 wip.update_queue = c.update_queue.clone();
-                wip.flags = c.flags.clone();
                 wip.child = c.child.clone();
                 wip.lanes = c.lanes.clone();
                 wip.child_lanes = c.child_lanes.clone();
@@ -266,11 +364,14 @@ impl FiberNode {
         Some(match &*state_node.unwrap().clone() {
             StateNode::FiberRootNode(root) => root.clone().borrow().container.clone(),
             StateNode::Element(ele) => ele.clone(),
+            StateNode::Portal(_, container) => container.clone(),
         })
     }
 }
 
 pub struct FiberRootNode {
+    // Like the host instances it's whatever the renderer created the root with, e.g. a DOM node,
+    // each root keeps its own so several of them can be mounted side by side
     pub container: Rc<dyn Any>,
     pub current: Rc<RefCell<FiberNode>>,
     pub finished_work: Option<Rc<RefCell<FiberNode>>>,
@@ -279,6 +380,24 @@ pub struct FiberRootNode {
     pub callback_node: Option<Task>,
     pub callback_priority: Lane,
     pub pending_passive_effects: Rc<RefCell<PendingPassiveEffects>>,
+    // wakeable -> Set<Lane>, avoid attaching the same ping listener twice
+    pub ping_cache: Option<WeakMap>,
+    // the time each lane expires, indexed by the position of the lane
+    pub expiration_times: Vec<f64>,
+    pub expired_lanes: Lane,
+    // pending lanes that are not worked on until they are pinged or the root is updated
+    pub suspended_lanes: Lane,
+    // suspended lanes that were retried after the wakeable they suspended on resolved
+    pub pinged_lanes: Lane,
+    // the commit delayed by the fallback throttle, cancelled when a new render starts
+    pub timeout_handle: Option<Task>,
+    // lanes that have to be rendered together, indexed by the position of the lane
+    pub entangled_lanes: Lane,
+    pub entanglements: Vec<Lane>,
+    // transition object -> lanes of its pending updates
+    pub pending_transitions: Vec<(JsValue, Lane)>,
+    // resource type -> value of getCacheForType, shared by the renders of the root until a commit
+    pub pooled_cache: Option<Map>,
 }
 
 impl FiberRootNode {
@@ -295,15 +414,109 @@ impl FiberRootNode {
             })),
             callback_node: None,
             callback_priority: Lane::NoLane,
+            ping_cache: None,
+            expiration_times: vec![NO_TIMESTAMP; TOTAL_LANES],
+            expired_lanes: Lane::NoLane,
+            suspended_lanes: Lane::NoLane,
+            pinged_lanes: Lane::NoLane,
+            timeout_handle: None,
+            entangled_lanes: Lane::NoLane,
+            entanglements: vec![Lane::NoLane; TOTAL_LANES],
+            pending_transitions: vec![],
+            pooled_cache: None,
         }
     }
 
-    pub fn mark_root_finished(&mut self, lane: Lane) {
-        self.pending_lanes &= !lane;
+    // The remaining lanes are the ones left in the finished tree, the lanes of the updates to fibers
+    // deleted by the commit are no longer pending, so the root doesn't render them again
+    pub fn mark_root_finished(&mut self, remaining_lanes: Lane) {
+        let no_longer_pending_lanes = self.pending_lanes.clone() & !remaining_lanes.clone();
+        self.pending_lanes = remaining_lanes;
+        self.expired_lanes &= self.pending_lanes.clone();
+        // The remaining lanes are worth another try after this commit
+        self.suspended_lanes = Lane::NoLane;
+        self.pinged_lanes = Lane::NoLane;
+        clear_expiration_times(no_longer_pending_lanes.clone(), &mut self.expiration_times);
+
+        self.entangled_lanes &= self.pending_lanes.clone();
+        let mut finished_lanes = no_longer_pending_lanes;
+        while finished_lanes != Lane::NoLane {
+            let lane = get_highest_priority(finished_lanes.clone());
+            self.entanglements[lane_to_index(lane.clone())] = Lane::NoLane;
+            finished_lanes -= lane;
+        }
+
+        let pending_lanes = self.pending_lanes.clone();
+        self.pending_transitions.retain_mut(|(_, lanes)| {
+            *lanes &= pending_lanes.clone();
+            *lanes != Lane::NoLane
+        });
     }
 
-    pub fn mark_root_updated(&mut self, lane: Lane) {
-        self.pending_lanes = merge_lanes(self.pending_lanes.clone(), lane)
+    pub fn mark_root_entangled(&mut self, entangled_lanes: Lane) {
+        self.entangled_lanes |= entangled_lanes.clone();
+        let mut lanes = entangled_lanes.clone();
+        while lanes != Lane::NoLane {
+            let lane = get_highest_priority(lanes.clone());
+            self.entanglements[lane_to_index(lane.clone())] |= entangled_lanes.clone();
+            lanes -= lane;
+        }
+    }
+
+    pub fn mark_starved_lanes_as_expired(&mut self, current_time: f64) {
+        let expired_lanes = mark_starved_lanes_as_expired(
+            self.pending_lanes.clone(),
+            &mut self.expiration_times,
+            current_time,
+        );
+        self.expired_lanes |= expired_lanes;
+    }
+
+    pub fn includes_expired_lane(&self, lanes: Lane) -> bool {
+        include_some_lanes(lanes, self.expired_lanes.clone())
+    }
+
+    pub fn mark_root_suspended(&mut self, suspended_lanes: Lane) {
+        self.suspended_lanes |= suspended_lanes.clone();
+        self.pinged_lanes &= !suspended_lanes.clone();
+        // A suspended lane doesn't starve, it waits on purpose
+        clear_expiration_times(suspended_lanes, &mut self.expiration_times);
+    }
+
+    pub fn mark_root_pinged(&mut self, pinged_lanes: Lane) {
+        self.pinged_lanes |= self.suspended_lanes.clone() & pinged_lanes;
+    }
+
+    // A transition render that only retries suspended content
+    pub fn includes_only_pinged_transitions(&self, lanes: Lane) -> bool {
+        lanes != Lane::NoLane
+            && is_subset_of_lanes(self.pinged_lanes.clone(), lanes.clone())
+            && Lane::TransitionLanes.contains(lanes)
+    }
+
+    pub fn mark_root_updated(&mut self, lane: Lane, transition: Option<JsValue>) {
+        self.pending_lanes = merge_lanes(self.pending_lanes.clone(), lane.clone());
+        // The update may unblock the suspended lanes, so they are tried again
+        if lane != Lane::IdleLane {
+            self.suspended_lanes = Lane::NoLane;
+            self.pinged_lanes = Lane::NoLane;
+        }
+
+        // Updates of the same transition are entangled with each other
+        if let Some(transition) = transition {
+            let same_transition = self
+                .pending_transitions
+                .iter_mut()
+                .find(|(pending_transition, _)| Object::is(pending_transition, &transition));
+            match same_transition {
+                Some((_, lanes)) => {
+                    *lanes |= lane;
+                    let entangled_lanes = lanes.clone();
+                    self.mark_root_entangled(entangled_lanes);
+                }
+                None => self.pending_transitions.push((transition, lane)),
+            }
+        }
     }
 
     pub fn get_next_lanes(&self) -> Lane {
@@ -312,7 +525,24 @@ impl FiberRootNode {
             return Lane::NoLane;
         }
 
-        let next_lanes = get_highest_priority(pending_lanes);
+        // The suspended lanes are skipped, unless nothing else is left and they have been pinged
+        let non_suspended_lanes = pending_lanes.clone() & !self.suspended_lanes.clone();
+        let next_lanes = if non_suspended_lanes != Lane::NoLane {
+            get_highest_priority(non_suspended_lanes)
+        } else {
+            get_highest_priority(pending_lanes.clone() & self.pinged_lanes.clone())
+        };
+        if next_lanes == Lane::NoLane {
+            return Lane::NoLane;
+        }
+
+        // The expired lanes are rendered along with the highest priority lane
+        let next_lanes = next_lanes | (pending_lanes.clone() & self.expired_lanes.clone());
+        let next_lanes = get_entangled_lanes(
+            next_lanes,
+            self.entangled_lanes.clone(),
+            &self.entanglements,
+        ) & pending_lanes;
 
         if next_lanes == Lane::NoLane {
             return Lane::NoLane;
@@ -2,14 +2,18 @@ use bitflags::bitflags;
 
 bitflags! {
     #[derive(Debug, Clone)]
-    pub struct Flags: u8 {
+    pub struct Flags: u32 {
         const NoFlags = 0b00000000;
         const Placement = 0b00000010;
         const Update = 0b00000100;
         const ChildDeletion = 0b00010000;
         const PassiveEffect = 0b00100000;
         const Ref = 0b01000000;
-        const LayoutMask = 0b01000000; // Ref
+        const Callback = 0b10000000;
+        const Visibility = 0b0000000100000000;
+        const DidCapture = 0b0000001000000000;
+        const ShouldCapture = 0b0000010000000000;
+        const LayoutMask = 0b11000100; // Update | Ref | Callback
         // effect hook
         const HookHasEffect = 0b00100001;
         const Passive = 0b00000010;
@@ -23,9 +27,20 @@ impl PartialEq for Flags {
 }
 
 pub fn get_mutation_mask() -> Flags {
-    Flags::Placement | Flags::Update | Flags::ChildDeletion
+    Flags::Placement | Flags::Update | Flags::ChildDeletion | Flags::Visibility
 }
 
 pub fn get_passive_mask() -> Flags {
     Flags::PassiveEffect | Flags::ChildDeletion
 }
+
+pub fn get_host_effect_mask() -> Flags {
+    Flags::Placement
+        | Flags::Update
+        | Flags::ChildDeletion
+        | Flags::PassiveEffect
+        | Flags::Ref
+        | Flags::Callback
+        | Flags::Visibility
+        | Flags::DidCapture
+}
@@ -3,30 +3,41 @@ use std::rc::Rc;
 
 use wasm_bindgen::prelude::{wasm_bindgen, Closure};
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::js_sys::{Array, Function, Object, Reflect};
+use web_sys::js_sys::{Array, Error, Function, Number, Object, Reflect};
 
-use shared::{derive_from_js_value, is_dev, log};
+use shared::{derive_from_js_value, error, is_dev, log, to_string, type_of, REACT_CONTEXT_TYPE};
 
 use crate::begin_work::mark_wip_received_update;
 use crate::fiber::{FiberNode, MemoizedState};
 use crate::fiber_context::read_context as read_context_origin;
 use crate::fiber_flags::Flags;
-use crate::fiber_lanes::{merge_lanes, remove_lanes, request_update_lane, Lane};
+use crate::fiber_lanes::{
+    get_current_update_priority, includes_only_non_urgent_lanes, merge_lanes, remove_lanes,
+    request_current_transition, request_update_lane, set_current_update_priority, Lane,
+};
+use crate::fiber_throw::is_thenable;
+use crate::type_of_mode::TypeOfMode;
 use crate::update_queue::{
-    create_update, create_update_queue, enqueue_update, process_update_queue,
-    ReturnOfProcessUpdateQueue, Update, UpdateQueue,
+    create_update, create_update_queue, enqueue_render_phase_update, enqueue_update,
+    mark_fiber_lanes, process_update_queue, ReturnOfProcessUpdateQueue, Update, UpdateQueue,
 };
-use crate::work_loop::schedule_update_on_fiber;
+use crate::work_loop::{request_cache_from_pool, request_deferred_lane, schedule_update_on_fiber};
 
 #[wasm_bindgen]
 extern "C" {
     fn updateDispatcher(args: &JsValue);
+    #[wasm_bindgen(catch)]
+    fn startTransition(scope: &Function) -> Result<(), JsValue>;
 }
 
 static mut CURRENTLY_RENDERING_FIBER: Option<Rc<RefCell<FiberNode>>> = None;
 static mut WORK_IN_PROGRESS_HOOK: Option<Rc<RefCell<Hook>>> = None;
 static mut CURRENT_HOOK: Option<Rc<RefCell<Hook>>> = None;
 static mut RENDER_LANE: Lane = Lane::NoLane;
+static mut DID_SCHEDULE_RENDER_PHASE_UPDATE_DURING_THIS_PASS: bool = false;
+
+static RE_RENDER_LIMIT: u32 = 25;
+static mut GLOBAL_CLIENT_ID_COUNTER: u32 = 0;
 
 #[derive(Debug, Clone)]
 pub struct Effect {
@@ -94,13 +105,20 @@ pub fn bailout_hook(wip: Rc<RefCell<FiberNode>>, render_lane: Lane) {
     current.borrow_mut().lanes = remove_lanes(lanes, render_lane);
 }
 
-fn update_hooks_to_dispatcher(is_update: bool) {
+// A re-render for render phase updates uses the update hooks, except for use_state which
+// applies those updates on top of the state of the previous pass
+fn update_hooks_to_dispatcher(is_update: bool, is_rerender: bool) {
     let object = Object::new();
 
     // use_state
-    let use_state_closure =
-        Closure::wrap(Box::new(if is_update { update_state } else { mount_state })
-            as Box<dyn Fn(&JsValue) -> Result<Vec<JsValue>, JsValue>>);
+    let use_state_closure = Closure::wrap(Box::new(if is_rerender {
+        rerender_state
+    } else if is_update {
+        update_state
+    } else {
+        mount_state
+    })
+        as Box<dyn Fn(&JsValue) -> Result<Vec<JsValue>, JsValue>>);
     let use_state = use_state_closure
         .as_ref()
         .unchecked_ref::<Function>()
@@ -156,6 +174,57 @@ fn update_hooks_to_dispatcher(is_update: bool) {
         .clone();
     use_context_clusure.forget();
 
+    // use_deferred_value
+    let use_deferred_value_closure = Closure::wrap(Box::new(if is_update {
+        update_deferred_value
+    } else {
+        mount_deferred_value
+    })
+        as Box<dyn Fn(JsValue, JsValue) -> JsValue>);
+    let use_deferred_value = use_deferred_value_closure
+        .as_ref()
+        .unchecked_ref::<Function>()
+        .clone();
+    use_deferred_value_closure.forget();
+
+    // use_id
+    let use_id_closure = Closure::wrap(
+        Box::new(if is_update { update_id } else { mount_id }) as Box<dyn Fn() -> JsValue>
+    );
+    let use_id = use_id_closure.as_ref().unchecked_ref::<Function>().clone();
+    use_id_closure.forget();
+
+    // use_transition
+    let use_transition_closure = Closure::wrap(Box::new(if is_rerender {
+        rerender_transition
+    } else if is_update {
+        update_transition
+    } else {
+        mount_transition
+    })
+        as Box<dyn Fn() -> Result<Vec<JsValue>, JsValue>>);
+    let use_transition = use_transition_closure
+        .as_ref()
+        .unchecked_ref::<Function>()
+        .clone();
+    use_transition_closure.forget();
+
+    // get_cache_for_type, the same in mount and update since it doesn't take a hook
+    let get_cache_for_type_closure = Closure::wrap(
+        Box::new(get_cache_for_type) as Box<dyn Fn(Function) -> Result<JsValue, JsValue>>
+    );
+    let get_cache_for_type = get_cache_for_type_closure
+        .as_ref()
+        .unchecked_ref::<Function>()
+        .clone();
+    get_cache_for_type_closure.forget();
+
+    // use, the same in mount and update since it doesn't take a hook
+    let use_closure =
+        Closure::wrap(Box::new(use_) as Box<dyn Fn(JsValue) -> Result<JsValue, JsValue>>);
+    let use_ = use_closure.as_ref().unchecked_ref::<Function>().clone();
+    use_closure.forget();
+
     Reflect::set(&object, &"use_state".into(), &use_state).expect("TODO: panic set use_state");
     Reflect::set(&object, &"use_effect".into(), &use_effect).expect("TODO: panic set use_effect");
     Reflect::set(&object, &"use_ref".into(), &use_ref).expect("TODO: panic set use_ref");
@@ -164,6 +233,14 @@ fn update_hooks_to_dispatcher(is_update: bool) {
         .expect("TODO: panic set use_callback");
     Reflect::set(&object, &"use_context".into(), &use_context)
         .expect("TODO: panic set use_context");
+    Reflect::set(&object, &"use_deferred_value".into(), &use_deferred_value)
+        .expect("TODO: panic set use_deferred_value");
+    Reflect::set(&object, &"use_id".into(), &use_id).expect("TODO: panic set use_id");
+    Reflect::set(&object, &"use_transition".into(), &use_transition)
+        .expect("TODO: panic set use_transition");
+    Reflect::set(&object, &"get_cache_for_type".into(), &get_cache_for_type)
+        .expect("TODO: panic set get_cache_for_type");
+    Reflect::set(&object, &"use".into(), &use_).expect("TODO: panic set use");
 
     updateDispatcher(&object.into());
 }
@@ -186,9 +263,9 @@ pub fn render_with_hooks(
 
     let current = work_in_progress_cloned.borrow().alternate.clone();
     if current.is_some() {
-        update_hooks_to_dispatcher(true);
+        update_hooks_to_dispatcher(true, false);
     } else {
-        update_hooks_to_dispatcher(false);
+        update_hooks_to_dispatcher(false, false);
     }
 
     let props;
@@ -198,9 +275,52 @@ pub fn render_with_hooks(
     }
 
     let component = JsValue::dyn_ref::<Function>(&Component).unwrap();
-    let children = component.call1(&JsValue::null(), &props);
+    let mut children = component.call1(&JsValue::null(), &props);
+
+    // StrictMode renders the component twice to surface side effects in render, the hooks of
+    // the first pass are reused and the children of the second pass are kept. The state
+    // initializers aren't run by the second pass, mount_state calls them twice instead.
+    if is_strict_mode_render() && children.is_ok() {
+        unsafe {
+            WORK_IN_PROGRESS_HOOK = None;
+            CURRENT_HOOK = None;
+        }
+        work_in_progress_cloned.borrow_mut().update_queue = None;
+        update_hooks_to_dispatcher(true, true);
+        children = component.call1(&JsValue::null(), &props);
+    }
+
+    // The state was updated while rendering, render again right away instead of committing
+    // the outdated children
+    let mut num_of_re_renders = 0;
+    while unsafe { DID_SCHEDULE_RENDER_PHASE_UPDATE_DURING_THIS_PASS } {
+        unsafe { DID_SCHEDULE_RENDER_PHASE_UPDATE_DURING_THIS_PASS = false };
+        if children.is_err() {
+            break;
+        }
+
+        num_of_re_renders += 1;
+        if num_of_re_renders >= RE_RENDER_LIMIT {
+            // Thrown like any other render error, so the render is unwound instead of committed
+            children = Err(Error::new(
+                "Too many re-renders. React limits the number of renders to prevent \
+                an infinite loop.",
+            )
+            .into());
+            break;
+        }
+
+        unsafe {
+            WORK_IN_PROGRESS_HOOK = None;
+            CURRENT_HOOK = None;
+        }
+        work_in_progress_cloned.borrow_mut().update_queue = None;
+        update_hooks_to_dispatcher(true, true);
+        children = component.call1(&JsValue::null(), &props);
+    }
 
     unsafe {
+        DID_SCHEDULE_RENDER_PHASE_UPDATE_DURING_THIS_PASS = false;
         CURRENTLY_RENDERING_FIBER = None;
         WORK_IN_PROGRESS_HOOK = None;
         CURRENT_HOOK = None;
@@ -210,6 +330,15 @@ pub fn render_with_hooks(
     children
 }
 
+fn is_strict_mode_render() -> bool {
+    is_dev()
+        && unsafe {
+            CURRENTLY_RENDERING_FIBER
+                .as_ref()
+                .is_some_and(|fiber| fiber.borrow().mode.contains(TypeOfMode::StrictLegacyMode))
+        }
+}
+
 fn mount_work_in_progress_hook() -> Option<Rc<RefCell<Hook>>> {
     let hook = Rc::new(RefCell::new(Hook::new(None, None, None, None, None)));
     unsafe {
@@ -327,10 +456,12 @@ fn mount_state(initial_state: &JsValue) -> Result<Vec<JsValue>, JsValue> {
     let memoized_state: JsValue;
 
     if initial_state.is_function() {
-        memoized_state = initial_state
-            .dyn_ref::<Function>()
-            .unwrap()
-            .call0(&JsValue::null())?;
+        let initializer = initial_state.dyn_ref::<Function>().unwrap();
+        memoized_state = initializer.call0(&JsValue::null())?;
+        // An impure initializer shows up as a different state, the first result is kept
+        if is_strict_mode_render() {
+            initializer.call0(&JsValue::null())?;
+        }
     } else {
         memoized_state = initial_state.clone();
     }
@@ -406,6 +537,7 @@ fn update_state(_: &JsValue) -> Result<Vec<JsValue>, JsValue> {
             memoized_state,
             base_state: new_base_state,
             base_queue: new_base_queue,
+            ..
         } = process_update_queue(
             base_state.clone(),
             base_queue,
@@ -458,6 +590,49 @@ fn update_state(_: &JsValue) -> Result<Vec<JsValue>, JsValue> {
     ])
 }
 
+fn rerender_state(_: &JsValue) -> Result<Vec<JsValue>, JsValue> {
+    let hook = update_work_in_progress_hook().expect("rerender_state hook is none");
+    let queue = { hook.borrow().update_queue.clone().unwrap() };
+    let dispatch = { queue.borrow().dispatch.clone() };
+    let prev_state = {
+        hook.borrow()
+            .memoized_state
+            .as_ref()
+            .and_then(|memoized_state| memoized_state.js_value())
+            .expect("rerender_state memoized_state is none")
+    };
+
+    let last_render_phase_update = { queue.borrow_mut().shared.pending.take() };
+    let last_render_phase_update = match last_render_phase_update {
+        Some(last_render_phase_update) => last_render_phase_update,
+        None => return Ok(vec![prev_state, dispatch.into()]),
+    };
+
+    let first_render_phase_update = { last_render_phase_update.borrow().next.clone().unwrap() };
+    let mut update = first_render_phase_update.clone();
+    let mut new_state = prev_state.clone();
+    loop {
+        let action = { update.borrow().action.clone().unwrap() };
+        new_state = basic_state_reducer(&new_state, &action)?;
+        let next = { update.borrow().next.clone().unwrap() };
+        if Rc::ptr_eq(&next, &first_render_phase_update) {
+            break;
+        }
+        update = next;
+    }
+
+    if !Object::is(&new_state, &prev_state) {
+        mark_wip_received_update();
+    }
+    hook.borrow_mut().memoized_state = Some(MemoizedState::MemoizedJsValue(new_state.clone()));
+    if hook.borrow().base_queue.is_none() {
+        hook.borrow_mut().base_state = Some(MemoizedState::MemoizedJsValue(new_state.clone()));
+    }
+    queue.borrow_mut().last_rendered_state = Some(new_state.clone());
+
+    Ok(vec![new_state, dispatch.into()])
+}
+
 pub fn basic_state_reducer(state: &JsValue, action: &JsValue) -> Result<JsValue, JsValue> {
     if action.is_function() {
         let function = action.dyn_ref::<Function>().unwrap();
@@ -466,13 +641,56 @@ pub fn basic_state_reducer(state: &JsValue, action: &JsValue) -> Result<JsValue,
     Ok(action.into())
 }
 
+// The fiber dispatching is the one being rendered, as opposed to e.g. an effect or an event
+fn is_render_phase_update(fiber: Rc<RefCell<FiberNode>>) -> bool {
+    let rendering_fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone() };
+    match rendering_fiber {
+        Some(rendering_fiber) => {
+            Rc::ptr_eq(&fiber, &rendering_fiber)
+                || match fiber.borrow().alternate.as_ref() {
+                    Some(alternate) => Rc::ptr_eq(alternate, &rendering_fiber),
+                    None => false,
+                }
+        }
+        None => false,
+    }
+}
+
+fn warn_about_render_phase_update_in_dev(fiber: Rc<RefCell<FiberNode>>) {
+    let rendering_fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone() };
+    if let Some(rendering_fiber) = rendering_fiber {
+        if !is_render_phase_update(fiber.clone()) {
+            let set_state_component_name = fiber.borrow().get_component_name();
+            let rendering_component_name = rendering_fiber.borrow().get_component_name();
+            error!(
+                "Cannot update a component (`{}`) while rendering a different component (`{}`). \
+                To locate the bad setState() call inside `{}`, follow the stack trace as \
+                described in https://reactjs.org/link/setstate-in-render",
+                set_state_component_name, rendering_component_name, rendering_component_name
+            );
+        }
+    }
+}
+
 fn dispatch_set_state(
     fiber: Rc<RefCell<FiberNode>>,
     update_queue: Rc<RefCell<UpdateQueue>>,
     action: &JsValue,
 ) {
+    if is_dev() {
+        warn_about_render_phase_update_in_dev(fiber.clone());
+    }
+
+    if is_render_phase_update(fiber.clone()) {
+        let update = create_update(action.clone(), unsafe { RENDER_LANE.clone() });
+        enqueue_render_phase_update(update_queue, update);
+        unsafe { DID_SCHEDULE_RENDER_PHASE_UPDATE_DURING_THIS_PASS = true };
+        return;
+    }
+
     let lane = request_update_lane();
     let mut update = create_update(action.clone(), lane.clone());
+    update.transition = request_current_transition();
     let current = { fiber.borrow().alternate.clone() };
     log!("dispatch_set_state action:{:?}", action);
     if fiber.borrow().lanes == Lane::NoLane
@@ -489,6 +707,7 @@ fn dispatch_set_state(
             let eager_state = eager_state.unwrap();
             update.has_eager_state = true;
             update.eager_state = Some(eager_state.clone());
+            // Objects are compared by identity, setting the same reference doesn't re-render
             if Object::is(&current_state, &eager_state) {
                 enqueue_update(update_queue.clone(), update, fiber.clone(), Lane::NoLane);
                 if is_dev() {
@@ -499,8 +718,9 @@ fn dispatch_set_state(
         }
     }
 
+    let transition = update.transition.clone();
     enqueue_update(update_queue.clone(), update, fiber.clone(), lane.clone());
-    schedule_update_on_fiber(fiber.clone(), lane);
+    schedule_update_on_fiber(fiber.clone(), lane, transition);
 }
 
 fn push_effect(
@@ -613,10 +833,15 @@ fn are_hook_inputs_equal(next_deps: &JsValue, pre_deps: &JsValue) -> bool {
         return false;
     }
 
-    let next_deps = next_deps.dyn_ref::<Array>().unwrap();
-    let pre_deps = pre_deps.dyn_ref::<Array>().unwrap();
+    let (next_deps, pre_deps) = match (next_deps.dyn_ref::<Array>(), pre_deps.dyn_ref::<Array>()) {
+        (Some(next_deps), Some(pre_deps)) => (next_deps, pre_deps),
+        _ => return false,
+    };
 
     let len = next_deps.length();
+    if len != pre_deps.length() {
+        return false;
+    }
 
     for i in 0..len {
         if Object::is(&pre_deps.get(i), &next_deps.get(i)) {
@@ -747,3 +972,235 @@ fn read_context(context: JsValue) -> JsValue {
     let consumer = unsafe { CURRENTLY_RENDERING_FIBER.clone() };
     read_context_origin(consumer, context)
 }
+
+fn is_rendering_deferred_work() -> bool {
+    unsafe { includes_only_non_urgent_lanes(RENDER_LANE.clone()) }
+}
+
+// Schedule a transition render of the current fiber, in which the deferred value catches up
+fn spawn_deferred_render() {
+    let fiber = unsafe { CURRENTLY_RENDERING_FIBER.clone().unwrap() };
+    let lane = request_deferred_lane();
+    mark_fiber_lanes(fiber.clone(), lane.clone());
+    schedule_update_on_fiber(fiber, lane, None);
+}
+
+fn mount_deferred_value(value: JsValue, initial_value: JsValue) -> JsValue {
+    let hook = mount_work_in_progress_hook();
+    let next_value = if initial_value.is_undefined() || is_rendering_deferred_work() {
+        value
+    } else {
+        spawn_deferred_render();
+        initial_value
+    };
+    hook.as_ref().unwrap().borrow_mut().memoized_state =
+        Some(MemoizedState::MemoizedJsValue(next_value.clone()));
+    next_value
+}
+
+fn update_deferred_value(value: JsValue, _initial_value: JsValue) -> JsValue {
+    let hook = update_work_in_progress_hook();
+    let prev_value = match hook.as_ref().unwrap().borrow().memoized_state.clone() {
+        Some(MemoizedState::MemoizedJsValue(prev_value)) => prev_value,
+        _ => panic!("update_deferred_value, memoized_state is not JsValue"),
+    };
+
+    if Object::is(&value, &prev_value) {
+        return value;
+    }
+
+    if is_rendering_deferred_work() {
+        hook.as_ref().unwrap().borrow_mut().memoized_state =
+            Some(MemoizedState::MemoizedJsValue(value.clone()));
+        mark_wip_received_update();
+        return value;
+    }
+
+    // Keep showing the previous value in the urgent render
+    spawn_deferred_render();
+    prev_value
+}
+
+// Without hydration there is no server id to match, so the id comes from a counter. It's kept in
+// the hook, which makes it stable however the siblings of the component change.
+fn mount_id() -> JsValue {
+    let hook = mount_work_in_progress_hook();
+    let global_client_id = unsafe {
+        let global_client_id = GLOBAL_CLIENT_ID_COUNTER;
+        GLOBAL_CLIENT_ID_COUNTER += 1;
+        global_client_id
+    };
+    let id = format!(
+        ":r{}:",
+        Number::from(global_client_id).to_string(32).unwrap()
+    );
+    let id = JsValue::from_str(&id);
+    hook.as_ref().unwrap().borrow_mut().memoized_state =
+        Some(MemoizedState::MemoizedJsValue(id.clone()));
+    id
+}
+
+fn update_id() -> JsValue {
+    let hook = update_work_in_progress_hook();
+    let memoized_state = { hook.as_ref().unwrap().borrow().memoized_state.clone() };
+    match memoized_state {
+        Some(MemoizedState::MemoizedJsValue(id)) => id,
+        _ => panic!("update_id, memoized_state is not JsValue"),
+    }
+}
+
+fn mount_transition() -> Result<Vec<JsValue>, JsValue> {
+    let state = mount_state(&JsValue::FALSE)?;
+    let set_pending = state[1].clone().dyn_into::<Function>()?;
+    let closure =
+        Closure::wrap(
+            Box::new(move |callback: Function| start_transition(&set_pending, callback))
+                as Box<dyn Fn(Function) -> Result<(), JsValue>>,
+        );
+    let start: JsValue = closure.as_ref().unchecked_ref::<Function>().clone().into();
+    closure.forget();
+
+    let hook = mount_work_in_progress_hook();
+    hook.as_ref().unwrap().borrow_mut().memoized_state =
+        Some(MemoizedState::MemoizedJsValue(start.clone()));
+    Ok(vec![state[0].clone(), start])
+}
+
+fn update_transition() -> Result<Vec<JsValue>, JsValue> {
+    let state = update_state(&JsValue::undefined())?;
+    Ok(vec![state[0].clone(), update_transition_start()])
+}
+
+fn rerender_transition() -> Result<Vec<JsValue>, JsValue> {
+    let state = rerender_state(&JsValue::undefined())?;
+    Ok(vec![state[0].clone(), update_transition_start()])
+}
+
+fn update_transition_start() -> JsValue {
+    let hook = update_work_in_progress_hook();
+    let memoized_state = { hook.as_ref().unwrap().borrow().memoized_state.clone() };
+    match memoized_state {
+        Some(MemoizedState::MemoizedJsValue(start)) => start,
+        _ => panic!("update_transition, memoized_state is not JsValue"),
+    }
+}
+
+fn start_transition(set_pending: &Function, callback: Function) -> Result<(), JsValue> {
+    // The pending state is shown with at least the priority of a continuous event
+    let prev_update_priority = get_current_update_priority();
+    if prev_update_priority == Lane::NoLane
+        || prev_update_priority.bits() > Lane::InputContinuousLane.bits()
+    {
+        set_current_update_priority(Lane::InputContinuousLane);
+    }
+    let result = set_pending.call1(&JsValue::null(), &JsValue::TRUE);
+    set_current_update_priority(prev_update_priority);
+    result?;
+
+    let set_pending = set_pending.clone();
+    let scope = Closure::wrap(Box::new(move || {
+        let result = callback.call0(&JsValue::null());
+        match &result {
+            // An async action stays pending until its promise settles
+            Ok(thenable) if is_thenable(thenable) => {
+                let set_pending = set_pending.clone();
+                let on_settled = Closure::wrap(Box::new(move |_: JsValue| {
+                    // The action is still pending here, so the reset is entangled with the
+                    // updates of its transition and they are committed together
+                    let set_pending = set_pending.clone();
+                    let reset = Closure::wrap(Box::new(move || {
+                        set_pending.call1(&JsValue::null(), &JsValue::FALSE)
+                    })
+                        as Box<dyn FnMut() -> Result<JsValue, JsValue>>);
+                    if let Err(e) = startTransition(reset.as_ref().unchecked_ref::<Function>()) {
+                        error!("Failed to reset the pending state of a transition {:?}", e);
+                    }
+                }) as Box<dyn FnMut(JsValue)>);
+                derive_from_js_value(thenable, "then")
+                    .dyn_ref::<Function>()
+                    .unwrap()
+                    .call2(
+                        thenable,
+                        on_settled.as_ref().unchecked_ref::<Function>(),
+                        on_settled.as_ref().unchecked_ref::<Function>(),
+                    )?;
+                on_settled.forget();
+            }
+            _ => {
+                set_pending.call1(&JsValue::null(), &JsValue::FALSE)?;
+            }
+        }
+        result
+    }) as Box<dyn FnMut() -> Result<JsValue, JsValue>>);
+    startTransition(scope.as_ref().unchecked_ref::<Function>())
+}
+
+// Every read of the same resource type in a render gets the value its first read created
+fn get_cache_for_type(resource_type: Function) -> Result<JsValue, JsValue> {
+    let cache = request_cache_from_pool();
+    let key: JsValue = resource_type.clone().into();
+    if cache.has(&key) {
+        return Ok(cache.get(&key));
+    }
+    let value = resource_type.call0(&JsValue::null())?;
+    cache.set(&key, &value);
+    Ok(value)
+}
+
+// Unlike the other hooks it doesn't take a hook slot, so it may be called conditionally
+fn use_(usable: JsValue) -> Result<JsValue, JsValue> {
+    if type_of(&usable, "object") || type_of(&usable, "function") {
+        if is_thenable(&usable) {
+            return use_thenable(usable);
+        }
+        if derive_from_js_value(&usable, "$$typeof") == REACT_CONTEXT_TYPE {
+            return Ok(read_context(usable));
+        }
+    }
+    Err(Error::new(&format!(
+        "An unsupported type was passed to use(): {}",
+        to_string(&usable)
+    ))
+    .into())
+}
+
+// The result is kept on the thenable, so the render after the ping reads it synchronously
+fn use_thenable(thenable: JsValue) -> Result<JsValue, JsValue> {
+    let status = derive_from_js_value(&thenable, "status");
+    if status == "fulfilled" {
+        return Ok(derive_from_js_value(&thenable, "value"));
+    }
+    if status == "rejected" {
+        return Err(derive_from_js_value(&thenable, "reason"));
+    }
+
+    if status.is_undefined() {
+        Reflect::set(&thenable, &"status".into(), &"pending".into()).expect("status panic");
+        let fulfilled_thenable = thenable.clone();
+        let on_fulfilled = Closure::wrap(Box::new(move |value: JsValue| {
+            Reflect::set(&fulfilled_thenable, &"status".into(), &"fulfilled".into())
+                .expect("status panic");
+            Reflect::set(&fulfilled_thenable, &"value".into(), &value).expect("value panic");
+        }) as Box<dyn FnMut(JsValue)>);
+        let rejected_thenable = thenable.clone();
+        let on_rejected = Closure::wrap(Box::new(move |reason: JsValue| {
+            Reflect::set(&rejected_thenable, &"status".into(), &"rejected".into())
+                .expect("status panic");
+            Reflect::set(&rejected_thenable, &"reason".into(), &reason).expect("reason panic");
+        }) as Box<dyn FnMut(JsValue)>);
+        let then = derive_from_js_value(&thenable, "then");
+        then.dyn_ref::<Function>()
+            .unwrap()
+            .call2(
+                &thenable,
+                on_fulfilled.as_ref().unchecked_ref::<Function>(),
+                on_rejected.as_ref().unchecked_ref::<Function>(),
+            )
+            .expect("Failed to track the thenable");
+        on_fulfilled.forget();
+        on_rejected.forget();
+    }
+
+    // Suspend, the component is rendered again once the thenable is settled
+    Err(thenable)
+}
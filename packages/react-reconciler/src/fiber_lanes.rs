@@ -1,5 +1,12 @@
 use bitflags::bitflags;
 use scheduler::{unstable_get_current_priority_level, Priority};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+
+#[wasm_bindgen]
+extern "C" {
+    fn getCurrentTransition() -> JsValue;
+}
 
 bitflags! {
     #[derive(Debug, Clone)]
@@ -8,6 +15,11 @@ bitflags! {
         const SyncLane =            0b0000000000000000000000000000001; // onClick
         const InputContinuousLane = 0b0000000000000000000000000000010; // Continuous Trigger, example: onScroll
         const DefaultLane =         0b0000000000000000000000000000100; // useEffect
+        const TransitionLane1 =     0b0000000000000000000000000001000; // startTransition
+        const TransitionLane2 =     0b0000000000000000000000000010000;
+        const TransitionLane3 =     0b0000000000000000000000000100000;
+        const TransitionLane4 =     0b0000000000000000000000001000000;
+        const TransitionLanes =     0b0000000000000000000000001111000;
         const IdleLane =            0b1000000000000000000000000000000;
     }
 }
@@ -20,6 +32,13 @@ impl PartialEq for Lane {
 
 impl Eq for Lane {}
 
+pub static TOTAL_LANES: usize = 31;
+pub static NO_TIMESTAMP: f64 = -1.0;
+
+static mut NEXT_TRANSITION_LANE: u32 = Lane::TransitionLane1.bits();
+// The lane of the updates set by the event system of the renderer, NoLane if not set
+static mut CURRENT_UPDATE_PRIORITY: u32 = Lane::NoLane.bits();
+
 pub fn get_highest_priority(lanes: Lane) -> Lane {
     let lanes = lanes.bits();
     let highest_priority = lanes & (lanes.wrapping_neg());
@@ -34,7 +53,48 @@ pub fn is_subset_of_lanes(set: Lane, subset: Lane) -> bool {
     (set & subset.clone()) == subset
 }
 
+// The transition object of the startTransition scope being executed
+pub fn request_current_transition() -> Option<JsValue> {
+    let transition = getCurrentTransition();
+    if transition.is_null() || transition.is_undefined() {
+        return None;
+    }
+    Some(transition)
+}
+
+// Each transition update claims the next transition lane, updates of the same transition are
+// entangled by the root so they are committed together
+pub fn claim_next_transition_lane() -> Lane {
+    unsafe {
+        let lane = Lane::from_bits_truncate(NEXT_TRANSITION_LANE);
+        NEXT_TRANSITION_LANE <<= 1;
+        if NEXT_TRANSITION_LANE & Lane::TransitionLanes.bits() == 0 {
+            NEXT_TRANSITION_LANE = Lane::TransitionLane1.bits();
+        }
+        lane
+    }
+}
+
+pub fn is_transition_lane(lane: Lane) -> bool {
+    lane != Lane::NoLane && Lane::TransitionLanes.contains(lane)
+}
+
+pub fn set_current_update_priority(lane: Lane) {
+    unsafe { CURRENT_UPDATE_PRIORITY = lane.bits() }
+}
+
+pub fn get_current_update_priority() -> Lane {
+    unsafe { Lane::from_bits_truncate(CURRENT_UPDATE_PRIORITY) }
+}
+
 pub fn request_update_lane() -> Lane {
+    if request_current_transition().is_some() {
+        return claim_next_transition_lane();
+    }
+    let update_priority = get_current_update_priority();
+    if update_priority != Lane::NoLane {
+        return update_priority;
+    }
     let current_scheduler_priority_level = unstable_get_current_priority_level();
     let update_lane = scheduler_priority_to_lane(current_scheduler_priority_level);
     update_lane
@@ -55,7 +115,7 @@ pub fn lanes_to_scheduler_priority(lanes: Lane) -> Priority {
         return Priority::ImmediatePriority;
     } else if lane == Lane::InputContinuousLane {
         return Priority::UserBlockingPriority;
-    } else if lane == Lane::DefaultLane {
+    } else if lane == Lane::DefaultLane || is_transition_lane(lane) {
         return Priority::NormalPriority;
     }
     Priority::IdlePriority
@@ -65,6 +125,128 @@ pub fn include_some_lanes(set: Lane, subset: Lane) -> bool {
     return (set & subset) != Lane::NoLane;
 }
 
+// Lanes of updates that are allowed to be deferred by useDeferredValue
+pub fn includes_only_non_urgent_lanes(lanes: Lane) -> bool {
+    let urgent_lanes = Lane::SyncLane | Lane::InputContinuousLane | Lane::DefaultLane;
+    lanes != Lane::NoLane && (lanes & urgent_lanes) == Lane::NoLane
+}
+
 pub fn remove_lanes(set: Lane, subset: Lane) -> Lane {
     return set - subset;
 }
+
+pub fn lane_to_index(lane: Lane) -> usize {
+    lane.bits().trailing_zeros() as usize
+}
+
+fn compute_expiration_time(lane: Lane, current_time: f64) -> f64 {
+    if lane == Lane::SyncLane || lane == Lane::InputContinuousLane {
+        current_time + 250.0
+    } else if lane == Lane::DefaultLane || is_transition_lane(lane) {
+        current_time + 5000.0
+    } else {
+        // Idle updates never expire
+        NO_TIMESTAMP
+    }
+}
+
+// Set the expiration time of the lanes that are pending for the first time, and
+// return the lanes that have been pending for longer than their expiration time
+pub fn mark_starved_lanes_as_expired(
+    pending_lanes: Lane,
+    expiration_times: &mut [f64],
+    current_time: f64,
+) -> Lane {
+    let mut expired_lanes = Lane::NoLane;
+    let mut lanes = pending_lanes;
+    while lanes != Lane::NoLane {
+        let lane = get_highest_priority(lanes.clone());
+        let index = lane_to_index(lane.clone());
+        let expiration_time = expiration_times[index];
+        if expiration_time == NO_TIMESTAMP {
+            expiration_times[index] = compute_expiration_time(lane.clone(), current_time);
+        } else if expiration_time <= current_time {
+            expired_lanes |= lane.clone();
+        }
+        lanes -= lane;
+    }
+    expired_lanes
+}
+
+pub fn clear_expiration_times(finished_lanes: Lane, expiration_times: &mut [f64]) {
+    let mut lanes = finished_lanes;
+    while lanes != Lane::NoLane {
+        let lane = get_highest_priority(lanes.clone());
+        expiration_times[lane_to_index(lane.clone())] = NO_TIMESTAMP;
+        lanes -= lane;
+    }
+}
+
+// Add the lanes that are entangled with the lanes to be rendered
+pub fn get_entangled_lanes(lanes: Lane, entangled_lanes: Lane, entanglements: &[Lane]) -> Lane {
+    let mut next_lanes = lanes.clone();
+    let mut lanes = lanes & entangled_lanes;
+    while lanes != Lane::NoLane {
+        let lane = get_highest_priority(lanes.clone());
+        next_lanes |= entanglements[lane_to_index(lane.clone())].clone();
+        lanes -= lane;
+    }
+    next_lanes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fiber_lanes::{
+        clear_expiration_times, get_entangled_lanes, mark_starved_lanes_as_expired, Lane,
+        NO_TIMESTAMP, TOTAL_LANES,
+    };
+
+    #[test]
+    fn test_preempted_transition_expires() {
+        let mut expiration_times = vec![NO_TIMESTAMP; TOTAL_LANES];
+        let pending_lanes = Lane::TransitionLane1;
+
+        // the transition keeps being preempted by higher priority work
+        for current_time in [0.0, 1000.0, 4999.0] {
+            let expired_lanes = mark_starved_lanes_as_expired(
+                pending_lanes.clone(),
+                &mut expiration_times,
+                current_time,
+            );
+            assert_eq!(expired_lanes, Lane::NoLane);
+        }
+
+        let expired_lanes =
+            mark_starved_lanes_as_expired(pending_lanes.clone(), &mut expiration_times, 5000.0);
+        assert_eq!(expired_lanes, Lane::TransitionLane1);
+
+        clear_expiration_times(pending_lanes.clone(), &mut expiration_times);
+        let expired_lanes =
+            mark_starved_lanes_as_expired(pending_lanes, &mut expiration_times, 6000.0);
+        assert_eq!(expired_lanes, Lane::NoLane);
+    }
+
+    #[test]
+    fn test_idle_lane_never_expires() {
+        let mut expiration_times = vec![NO_TIMESTAMP; TOTAL_LANES];
+        mark_starved_lanes_as_expired(Lane::IdleLane, &mut expiration_times, 0.0);
+        let expired_lanes =
+            mark_starved_lanes_as_expired(Lane::IdleLane, &mut expiration_times, 1e9);
+        assert_eq!(expired_lanes, Lane::NoLane);
+    }
+
+    #[test]
+    fn test_entangled_lanes_are_rendered_together() {
+        let mut entanglements = vec![Lane::NoLane; TOTAL_LANES];
+        let entangled = Lane::TransitionLane1 | Lane::TransitionLane3;
+        entanglements[3] = entangled.clone();
+        entanglements[5] = entangled.clone();
+
+        let next_lanes =
+            get_entangled_lanes(Lane::TransitionLane1, entangled.clone(), &entanglements);
+        assert_eq!(next_lanes, Lane::TransitionLane1 | Lane::TransitionLane3);
+
+        let next_lanes = get_entangled_lanes(Lane::TransitionLane2, entangled, &entanglements);
+        assert_eq!(next_lanes, Lane::TransitionLane2);
+    }
+}
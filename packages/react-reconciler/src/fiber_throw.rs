@@ -0,0 +1,113 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::js_sys::{Function, Set, WeakMap};
+
+use shared::{derive_from_js_value, log, type_of};
+
+use crate::fiber::{FiberNode, FiberRootNode};
+use crate::fiber_flags::Flags;
+use crate::fiber_lanes::{merge_lanes, Lane};
+use crate::suspense_context::get_suspense_handler;
+use crate::work_loop::{ensure_root_is_scheduled, mark_update_lane_from_fiber_to_root};
+
+pub fn is_thenable(value: &JsValue) -> bool {
+    (type_of(value, "object") || type_of(value, "function"))
+        && !value.is_null()
+        && derive_from_js_value(value, "then").is_function()
+}
+
+pub fn throw_exception(root: Rc<RefCell<FiberRootNode>>, value: JsValue, lane: Lane) {
+    // TODO: errors need error boundaries, i.e. class components with getDerivedStateFromError.
+    // A boundary remounted with a new key is a new fiber, so it would retry without the error.
+    if !is_thenable(&value) {
+        log!("throw_exception only supports thenable, got {:?}", value);
+        return;
+    }
+
+    let suspense_boundary = get_suspense_handler();
+    if suspense_boundary.is_none() {
+        log!("A component suspended, but no Suspense boundary was found");
+        return;
+    }
+    let suspense_boundary = suspense_boundary.unwrap();
+    suspense_boundary.borrow_mut().flags |= Flags::ShouldCapture;
+    attach_ping_listener(root, value, lane, suspense_boundary);
+}
+
+fn attach_ping_listener(
+    root: Rc<RefCell<FiberRootNode>>,
+    wakeable: JsValue,
+    lane: Lane,
+    suspense_boundary: Rc<RefCell<FiberNode>>,
+) {
+    let ping_cache = { root.borrow().ping_cache.clone() };
+    let ping_cache = match ping_cache {
+        Some(ping_cache) => ping_cache,
+        None => {
+            let ping_cache = WeakMap::new();
+            root.borrow_mut().ping_cache = Some(ping_cache.clone());
+            ping_cache
+        }
+    };
+
+    let wakeable_object = wakeable.unchecked_ref();
+    let mut thread_ids = ping_cache.get(wakeable_object);
+    if thread_ids.is_undefined() {
+        thread_ids = Set::new(&JsValue::undefined()).into();
+        ping_cache.set(wakeable_object, &thread_ids);
+    }
+    let thread_ids = thread_ids.dyn_into::<Set>().unwrap();
+
+    let lane_js_value = JsValue::from(lane.bits());
+    if thread_ids.has(&lane_js_value) {
+        return;
+    }
+    // The same wakeable may be thrown repeatedly while it is pending, only attach once per lane
+    thread_ids.add(&lane_js_value);
+
+    let wakeable_cloned = wakeable.clone();
+    let closure = Closure::wrap(Box::new(move |_: JsValue| {
+        if let Some(ping_cache) = root.borrow().ping_cache.clone() {
+            ping_cache.delete(wakeable_cloned.unchecked_ref());
+        }
+        ping_suspended_root(root.clone(), suspense_boundary.clone(), lane.clone());
+    }) as Box<dyn FnMut(JsValue)>);
+    let ping = closure.as_ref().unchecked_ref::<Function>().clone();
+    closure.forget();
+
+    let then = derive_from_js_value(&wakeable, "then");
+    then.dyn_ref::<Function>()
+        .unwrap()
+        .call2(&wakeable, &ping, &ping)
+        .expect("Failed to attach ping listener");
+}
+
+fn ping_suspended_root(
+    root: Rc<RefCell<FiberRootNode>>,
+    suspense_boundary: Rc<RefCell<FiberNode>>,
+    lane: Lane,
+) {
+    // The boundary has no pending update of its own, mark it so that the retry
+    // render doesn't bailout before reaching the suspended subtree
+    {
+        let lanes = suspense_boundary.borrow().lanes.clone();
+        suspense_boundary.borrow_mut().lanes = merge_lanes(lanes, lane.clone());
+        let alternate = suspense_boundary.borrow().alternate.clone();
+        if let Some(alternate) = alternate {
+            let lanes = alternate.borrow().lanes.clone();
+            alternate.borrow_mut().lanes = merge_lanes(lanes, lane.clone());
+        }
+    }
+    mark_update_lane_from_fiber_to_root(suspense_boundary, lane.clone());
+    // Only the lane that was waiting on the wakeable is tried again, the other suspended lanes
+    // still wait on theirs. Its expiration time is kept, so the starvation clock doesn't restart.
+    {
+        let pending_lanes = root.borrow().pending_lanes.clone();
+        root.borrow_mut().pending_lanes = merge_lanes(pending_lanes, lane.clone());
+    }
+    root.borrow_mut().mark_root_pinged(lane);
+    ensure_root_is_scheduled(root);
+}
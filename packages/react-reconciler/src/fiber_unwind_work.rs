@@ -0,0 +1,45 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use shared::derive_from_js_value;
+
+use crate::fiber::FiberNode;
+use crate::fiber_context::pop_provider;
+use crate::fiber_flags::Flags;
+use crate::suspense_context::pop_suspense_handler;
+use crate::work_tags::WorkTag;
+
+pub fn unwind_work(wip: Rc<RefCell<FiberNode>>) -> Option<Rc<RefCell<FiberNode>>> {
+    let flags = wip.borrow().flags.clone();
+    let tag = wip.borrow().tag.clone();
+    match tag {
+        WorkTag::SuspenseComponent => {
+            pop_suspense_handler();
+            if flags.contains(Flags::ShouldCapture) && !flags.contains(Flags::DidCapture) {
+                wip.borrow_mut().flags = (flags - Flags::ShouldCapture) | Flags::DidCapture;
+                return Some(wip);
+            }
+            None
+        }
+        WorkTag::ContextProvider => {
+            let context = derive_from_js_value(&wip.borrow()._type, "_context");
+            pop_provider(&context);
+            None
+        }
+        _ => None,
+    }
+}
+
+// Pops what begin_work pushed for a fiber whose render is abandoned, so the stacks are back at the
+// level they were before the render started
+pub fn unwind_interrupted_work(interrupted_work: Rc<RefCell<FiberNode>>) {
+    let tag = interrupted_work.borrow().tag.clone();
+    match tag {
+        WorkTag::SuspenseComponent => pop_suspense_handler(),
+        WorkTag::ContextProvider => {
+            let context = derive_from_js_value(&interrupted_work.borrow()._type, "_context");
+            pop_provider(&context);
+        }
+        _ => {}
+    }
+}
@@ -3,26 +3,37 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use wasm_bindgen::JsValue;
+use web_sys::js_sys::Function;
+
+use shared::error;
 
 use crate::complete_work::CompleteWork;
 use crate::fiber::{FiberNode, FiberRootNode, StateNode};
 // use crate::fiber_hooks::{WORK_LOOP as Fiber_HOOKS};
 use crate::fiber_lanes::Lane;
+use crate::sync_task_queue::flush_sync_callbacks;
+use crate::type_of_mode::TypeOfMode;
 use crate::update_queue::{create_update, create_update_queue, enqueue_update};
-use crate::work_loop::schedule_update_on_fiber;
+use crate::work_loop::{flush_passive_effects, is_already_rendering, schedule_update_on_fiber};
 use crate::work_tags::WorkTag;
 
+pub use crate::work_loop::{batched_updates, flush_sync};
+
 mod begin_work;
 mod child_fiber;
 mod commit_work;
 mod complete_work;
 pub mod fiber;
 mod fiber_context;
-mod fiber_flags;
+pub mod fiber_flags;
 mod fiber_hooks;
 pub mod fiber_lanes;
+mod fiber_throw;
+mod fiber_unwind_work;
 mod hook_effect_tags;
+mod suspense_context;
 mod sync_task_queue;
+pub mod type_of_mode;
 mod update_queue;
 mod work_loop;
 pub mod work_tags;
@@ -30,10 +41,20 @@ pub mod work_tags;
 pub static mut HOST_CONFIG: Option<Rc<dyn HostConfig>> = None;
 static mut COMPLETE_WORK: Option<CompleteWork> = None;
 
+// TODO: renderers only mount, when hydration is added its methods and code paths have to be
+// gated behind a supports_hydration capability, so that e.g. the noop renderer doesn't need them
 pub trait HostConfig {
     fn create_text_instance(&self, content: &JsValue) -> Rc<dyn Any>;
     fn create_instance(&self, _type: String, props: Rc<dyn Any>) -> Rc<dyn Any>;
     fn append_initial_child(&self, parent: Rc<dyn Any>, child: Rc<dyn Any>);
+    // Returns true if commit_mount should be called once the instance has been inserted
+    fn finalize_initial_children(
+        &self,
+        instance: Rc<dyn Any>,
+        _type: String,
+        props: &JsValue,
+    ) -> bool;
+    fn commit_mount(&self, instance: Rc<dyn Any>, _type: String, props: &JsValue);
     fn append_child_to_container(&self, child: Rc<dyn Any>, parent: Rc<dyn Any>);
     fn remove_child(&self, child: Rc<dyn Any>, container: Rc<dyn Any>);
     // fn commit_text_update(&self, text_instance: Rc<dyn Any>, content: &JsValue);
@@ -45,6 +66,20 @@ pub trait HostConfig {
         before: Rc<dyn Any>,
     );
     fn schedule_microtask(&self, callback: Box<dyn FnMut()>);
+    fn hide_instance(&self, instance: Rc<dyn Any>);
+    fn unhide_instance(&self, instance: Rc<dyn Any>, props: &JsValue);
+    fn hide_text_instance(&self, text_instance: Rc<dyn Any>);
+    fn unhide_text_instance(&self, text_instance: Rc<dyn Any>, content: &JsValue);
+    // Returns the host instance the children of a portal into `container_info` are placed into
+    fn prepare_portal_mount(&self, container_info: &JsValue) -> Rc<dyn Any>;
+    // The value that is attached to the ref of a host component
+    fn get_public_instance(&self, instance: Rc<dyn Any>) -> JsValue;
+    // Called around the mutation phase, every host update of a commit is applied in between
+    fn prepare_for_commit(&self, container: Rc<dyn Any>);
+    fn reset_after_commit(&self, container: Rc<dyn Any>);
+    // Called for every host instance of a deleted subtree once it has been removed, the instance
+    // is never used again so the renderer can release what it holds for it
+    fn detach_deleted_instance(&self, instance: Rc<dyn Any>);
 }
 
 pub struct Reconciler {
@@ -62,6 +97,7 @@ impl Reconciler {
             JsValue::null(),
             JsValue::null(),
         )));
+        host_root_fiber.borrow_mut().mode = TypeOfMode::ConcurrentMode;
         host_root_fiber.clone().borrow_mut().update_queue = Some(create_update_queue());
         let root = Rc::new(RefCell::new(FiberRootNode::new(
             container.clone(),
@@ -73,9 +109,20 @@ impl Reconciler {
     }
 
     pub fn update_container(&self, element: JsValue, root: Rc<RefCell<FiberRootNode>>) -> JsValue {
+        self.update_container_with_callback(element, root, None)
+    }
+
+    // callback is called in the layout phase once the update has been committed
+    pub fn update_container_with_callback(
+        &self,
+        element: JsValue,
+        root: Rc<RefCell<FiberRootNode>>,
+        callback: Option<Function>,
+    ) -> JsValue {
         let host_root_fiber = Rc::clone(&root).borrow().current.clone();
         let root_render_priority = Lane::SyncLane;
-        let update = create_update(element.clone(), root_render_priority.clone());
+        let mut update = create_update(element.clone(), root_render_priority.clone());
+        update.callback = callback;
         let update_queue = { host_root_fiber.borrow().update_queue.clone().unwrap() };
         enqueue_update(
             update_queue,
@@ -86,8 +133,27 @@ impl Reconciler {
         unsafe {
             HOST_CONFIG = Some(self.host_config.clone());
             COMPLETE_WORK = Some(CompleteWork::new(self.host_config.clone()));
-            schedule_update_on_fiber(host_root_fiber, root_render_priority);
+            schedule_update_on_fiber(host_root_fiber, root_render_priority, None);
         }
         element.clone()
     }
+
+    pub fn unmount_container(&self, root: Rc<RefCell<FiberRootNode>>) -> JsValue {
+        if is_already_rendering() {
+            error!(
+                "Attempted to synchronously unmount a root while React was already rendering. \
+                React cannot finish unmounting the root until the current render has completed."
+            );
+        }
+
+        // Run the effects of the last commit first, so every mounted effect has its destroy
+        let pending_passive_effects = { root.borrow().pending_passive_effects.clone() };
+        flush_passive_effects(pending_passive_effects.clone());
+
+        self.update_container(JsValue::null(), root.clone());
+        // Delete the tree synchronously, then run the destroys collected in the deletion traversal
+        flush_sync_callbacks();
+        flush_passive_effects(pending_passive_effects);
+        JsValue::undefined()
+    }
 }
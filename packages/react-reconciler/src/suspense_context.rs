@@ -0,0 +1,27 @@
+use std::{cell::RefCell, rc::Rc};
+
+use shared::log;
+
+use crate::fiber::FiberNode;
+
+static mut SUSPENSE_HANDLER_STACK: Vec<Rc<RefCell<FiberNode>>> = vec![];
+
+pub fn get_suspense_handler() -> Option<Rc<RefCell<FiberNode>>> {
+    unsafe { SUSPENSE_HANDLER_STACK.last().cloned() }
+}
+
+pub fn push_suspense_handler(handler: Rc<RefCell<FiberNode>>) {
+    unsafe { SUSPENSE_HANDLER_STACK.push(handler) }
+}
+
+pub fn pop_suspense_handler() {
+    unsafe {
+        if SUSPENSE_HANDLER_STACK.pop().is_none() {
+            log!("pop_suspense_handler with an empty stack")
+        }
+    }
+}
+
+pub fn reset_suspense_handler_stack() {
+    unsafe { SUSPENSE_HANDLER_STACK = vec![] }
+}
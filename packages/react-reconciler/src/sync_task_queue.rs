@@ -9,11 +9,21 @@ pub fn flush_sync_callbacks() {
     unsafe {
         if !IS_FLUSHING_SYNC_QUEUE && !SYNC_QUEUE.is_empty() {
             IS_FLUSHING_SYNC_QUEUE = true;
-            for callback in SYNC_QUEUE.iter_mut() {
+            // Callbacks scheduled while flushing go to a new queue, which is flushed in their own micro task
+            let mut queue = std::mem::take(&mut SYNC_QUEUE);
+            for callback in queue.iter_mut() {
                 callback();
             }
-            SYNC_QUEUE = vec![];
             IS_FLUSHING_SYNC_QUEUE = false;
         }
     }
 }
+
+// The flushed callbacks may schedule more sync work, e.g. a layout effect updating another root
+pub fn flush_sync_callbacks_until_idle() {
+    unsafe {
+        while !IS_FLUSHING_SYNC_QUEUE && !SYNC_QUEUE.is_empty() {
+            flush_sync_callbacks();
+        }
+    }
+}
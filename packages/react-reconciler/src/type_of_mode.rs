@@ -0,0 +1,19 @@
+use bitflags::bitflags;
+
+bitflags! {
+    // The mode of a fiber is inherited from its parent, mode components like StrictMode add to it
+    #[derive(Debug, Clone)]
+    pub struct TypeOfMode: u8 {
+        const NoMode = 0b0000000;
+        const ConcurrentMode = 0b0000001;
+        const ProfileMode = 0b0000010;
+        const StrictLegacyMode = 0b0001000;
+        const StrictEffectsMode = 0b0010000;
+    }
+}
+
+impl PartialEq for TypeOfMode {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits() == other.bits()
+    }
+}
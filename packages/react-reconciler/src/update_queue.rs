@@ -9,6 +9,16 @@ use shared::log;
 use crate::fiber::{FiberNode, MemoizedState};
 use crate::fiber_hooks::{basic_state_reducer, Effect};
 use crate::fiber_lanes::{is_subset_of_lanes, merge_lanes, Lane};
+use crate::work_loop::{is_interleaved_update, mark_update_lane_from_fiber_to_root};
+
+// a queue with interleaved updates, and the lanes of those updates
+struct InterleavedQueue {
+    fiber: Rc<RefCell<FiberNode>>,
+    update_queue: Rc<RefCell<UpdateQueue>>,
+    lanes: Lane,
+}
+
+static mut INTERLEAVED_QUEUES: Vec<InterleavedQueue> = vec![];
 
 #[derive(Clone, Debug)]
 pub struct UpdateAction;
@@ -20,11 +30,16 @@ pub struct Update {
     pub next: Option<Rc<RefCell<Update>>>,
     pub has_eager_state: bool,
     pub eager_state: Option<JsValue>,
+    pub callback: Option<Function>,
+    // the transition object of the startTransition the update is scheduled in
+    pub transition: Option<JsValue>,
 }
 
 #[derive(Clone, Debug)]
 pub struct UpdateType {
     pub pending: Option<Rc<RefCell<Update>>>,
+    // updates enqueued while a render is in progress, moved to pending before the next render
+    pub interleaved: Option<Rc<RefCell<Update>>>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,6 +48,8 @@ pub struct UpdateQueue {
     pub dispatch: Option<Function>,
     pub last_effect: Option<Rc<RefCell<Effect>>>,
     pub last_rendered_state: Option<JsValue>,
+    // callbacks of the processed updates, called in the layout phase
+    pub callbacks: Vec<Function>,
 }
 
 pub fn create_update(action: JsValue, lane: Lane) -> Update {
@@ -42,6 +59,8 @@ pub fn create_update(action: JsValue, lane: Lane) -> Update {
         next: None,
         has_eager_state: false,
         eager_state: None,
+        callback: None,
+        transition: None,
     }
 }
 
@@ -51,18 +70,82 @@ pub fn enqueue_update(
     fiber: Rc<RefCell<FiberNode>>,
     lane: Lane,
 ) {
-    let pending = update_queue.borrow().shared.pending.clone();
     let update_rc = Rc::new(RefCell::new(update));
-    let update_option = Option::from(update_rc.clone());
-    if pending.is_none() {
-        update_rc.borrow_mut().next = update_option.clone();
-    } else {
-        let pending = pending.clone().unwrap();
-        update_rc.borrow_mut().next = pending.borrow().next.clone();
-        pending.borrow_mut().next = update_option.clone();
+    if is_interleaved_update() {
+        // The render in progress must not see this update, so it's kept aside
+        // and the fiber lanes are marked when it's transferred to pending
+        let interleaved = update_queue.borrow().shared.interleaved.clone();
+        update_queue.borrow_mut().shared.interleaved = Some(append_to_ring(interleaved, update_rc));
+        unsafe {
+            match INTERLEAVED_QUEUES
+                .iter_mut()
+                .find(|queue| Rc::ptr_eq(&queue.update_queue, &update_queue))
+            {
+                Some(queue) => queue.lanes = merge_lanes(queue.lanes.clone(), lane),
+                None => INTERLEAVED_QUEUES.push(InterleavedQueue {
+                    fiber,
+                    update_queue,
+                    lanes: lane,
+                }),
+            }
+        }
+        return;
     }
-    update_queue.borrow_mut().shared.pending = update_option.clone();
 
+    let pending = update_queue.borrow().shared.pending.clone();
+    update_queue.borrow_mut().shared.pending = Some(append_to_ring(pending, update_rc));
+    mark_fiber_lanes(fiber, lane);
+}
+
+// The component is re-rendered with its render phase updates before it completes, so they
+// neither wait in the interleaved queue nor mark the fiber lanes
+pub fn enqueue_render_phase_update(update_queue: Rc<RefCell<UpdateQueue>>, update: Update) {
+    let pending = update_queue.borrow().shared.pending.clone();
+    update_queue.borrow_mut().shared.pending =
+        Some(append_to_ring(pending, Rc::new(RefCell::new(update))));
+}
+
+// last -> first, returns the new last
+fn append_to_ring(
+    last: Option<Rc<RefCell<Update>>>,
+    update: Rc<RefCell<Update>>,
+) -> Rc<RefCell<Update>> {
+    match last {
+        None => update.borrow_mut().next = Some(update.clone()),
+        Some(last) => {
+            update.borrow_mut().next = last.borrow().next.clone();
+            last.borrow_mut().next = Some(update.clone());
+        }
+    }
+    update
+}
+
+pub fn finish_queueing_interleaved_updates() {
+    let interleaved_queues = unsafe { std::mem::take(&mut INTERLEAVED_QUEUES) };
+    for InterleavedQueue {
+        fiber,
+        update_queue,
+        lanes,
+    } in interleaved_queues
+    {
+        let interleaved = update_queue.borrow_mut().shared.interleaved.take();
+        if let Some(last_interleaved) = interleaved {
+            let pending = update_queue.borrow().shared.pending.clone();
+            if let Some(last_pending) = pending {
+                // pending = p1 -> p0, interleaved = i1 -> i0, result = i1 -> p0 -> p1 -> i0
+                let first_interleaved = last_interleaved.borrow().next.clone();
+                let first_pending = last_pending.borrow().next.clone();
+                last_pending.borrow_mut().next = first_interleaved;
+                last_interleaved.borrow_mut().next = first_pending;
+            }
+            update_queue.borrow_mut().shared.pending = Some(last_interleaved);
+        }
+        mark_fiber_lanes(fiber.clone(), lanes.clone());
+        mark_update_lane_from_fiber_to_root(fiber, lanes);
+    }
+}
+
+pub fn mark_fiber_lanes(fiber: Rc<RefCell<FiberNode>>, lane: Lane) {
     let fiber_lane = { fiber.borrow().lanes.clone() };
     fiber.borrow_mut().lanes = merge_lanes(fiber_lane, lane.clone());
     let alternate = fiber.borrow().alternate.clone();
@@ -75,10 +158,14 @@ pub fn enqueue_update(
 
 pub fn create_update_queue() -> Rc<RefCell<UpdateQueue>> {
     Rc::new(RefCell::new(UpdateQueue {
-        shared: UpdateType { pending: None },
+        shared: UpdateType {
+            pending: None,
+            interleaved: None,
+        },
         dispatch: None,
         last_effect: None,
         last_rendered_state: None,
+        callbacks: vec![],
     }))
 }
 
@@ -86,6 +173,7 @@ pub struct ReturnOfProcessUpdateQueue {
     pub memoized_state: Option<MemoizedState>,
     pub base_state: Option<MemoizedState>,
     pub base_queue: Option<Rc<RefCell<Update>>>,
+    pub callbacks: Vec<Function>,
 }
 
 pub fn process_update_queue(
@@ -98,6 +186,7 @@ pub fn process_update_queue(
         memoized_state: base_state.clone(),
         base_state: base_state.clone(),
         base_queue: None,
+        callbacks: vec![],
     };
 
     if pending_update.is_some() {
@@ -122,6 +211,8 @@ pub fn process_update_queue(
                     update.borrow().action.clone().unwrap(),
                     update_lane.clone(),
                 )));
+                clone.borrow_mut().callback = update.borrow().callback.clone();
+                clone.borrow_mut().transition = update.borrow().transition.clone();
 
                 if on_skip_update.is_some() {
                     let function = on_skip_update.unwrap();
@@ -145,6 +236,10 @@ pub fn process_update_queue(
                     new_base_queue_last = Some(clone.clone())
                 }
 
+                if let Some(callback) = update.borrow().callback.clone() {
+                    result.callbacks.push(callback);
+                }
+
                 if update.borrow().has_eager_state {
                     new_state = Some(MemoizedState::MemoizedJsValue(
                         update.borrow().eager_state.clone().unwrap(),
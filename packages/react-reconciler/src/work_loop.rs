@@ -4,13 +4,13 @@ use std::rc::Rc;
 use bitflags::bitflags;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::js_sys::Function;
+use web_sys::js_sys::{Function, Map};
 
 use scheduler::{
-    unstable_cancel_callback, unstable_schedule_callback_no_delay, unstable_should_yield_to_host,
-    Priority,
+    unstable_cancel_callback, unstable_now, unstable_run_with_priority, unstable_schedule_callback,
+    unstable_schedule_callback_no_delay, unstable_should_yield_to_host, Priority,
 };
-use shared::{is_dev, log};
+use shared::{error, is_dev, log};
 
 use crate::begin_work::begin_work;
 use crate::commit_work::{
@@ -18,9 +18,18 @@ use crate::commit_work::{
     commit_hook_effect_list_unmount, commit_layout_effects, commit_mutation_effects,
 };
 use crate::fiber::{FiberNode, FiberRootNode, PendingPassiveEffects, StateNode};
-use crate::fiber_flags::{get_mutation_mask, get_passive_mask, Flags};
-use crate::fiber_lanes::{get_highest_priority, lanes_to_scheduler_priority, merge_lanes, Lane};
-use crate::sync_task_queue::{flush_sync_callbacks, schedule_sync_callback};
+use crate::fiber_flags::{get_host_effect_mask, get_mutation_mask, get_passive_mask, Flags};
+use crate::fiber_lanes::{
+    claim_next_transition_lane, get_highest_priority, include_some_lanes,
+    lanes_to_scheduler_priority, merge_lanes, Lane,
+};
+use crate::fiber_throw::{is_thenable, throw_exception};
+use crate::fiber_unwind_work::{unwind_interrupted_work, unwind_work};
+use crate::suspense_context::{get_suspense_handler, reset_suspense_handler_stack};
+use crate::sync_task_queue::{
+    flush_sync_callbacks, flush_sync_callbacks_until_idle, schedule_sync_callback,
+};
+use crate::update_queue::finish_queueing_interleaved_updates;
 use crate::work_tags::WorkTag;
 use crate::{COMPLETE_WORK, HOST_CONFIG};
 
@@ -28,6 +37,7 @@ bitflags! {
     #[derive(Debug, Clone)]
     pub struct ExecutionContext: u8 {
         const NoContext = 0b0000;
+        const BatchedContext = 0b0001;
         const RenderContext = 0b0010;
         const CommitContext = 0b0100;
         const ChildDeletion = 0b00010000;
@@ -41,14 +51,40 @@ impl PartialEq for ExecutionContext {
 }
 
 static mut WORK_IN_PROGRESS: Option<Rc<RefCell<FiberNode>>> = None;
+static mut WORK_IN_PROGRESS_ROOT: Option<Rc<RefCell<FiberRootNode>>> = None;
 static mut WORK_IN_PROGRESS_ROOT_RENDER_LANE: Lane = Lane::NoLane;
+// lanes of the updates scheduled while the root is being rendered
+static mut WORK_IN_PROGRESS_ROOT_INTERLEAVED_UPDATED_LANES: Lane = Lane::NoLane;
+// the lane of the deferred renders spawned by the render in progress
+static mut WORK_IN_PROGRESS_DEFERRED_LANE: Lane = Lane::NoLane;
 static mut EXECUTION_CONTEXT: ExecutionContext = ExecutionContext::NoContext;
-static mut ROOT_DOES_HAVE_PASSIVE_EFFECTS: bool = false;
+// The root committed last with passive effects, they are flushed in a later task
+static mut ROOT_WITH_PENDING_PASSIVE_EFFECTS: Option<Rc<RefCell<FiberRootNode>>> = None;
+static mut WORK_IN_PROGRESS_SUSPENDED_REASON: u8 = NOT_SUSPENDED;
+static mut WORK_IN_PROGRESS_THROWN_VALUE: Option<JsValue> = None;
+// sync renders triggered by the layout phase of the same root, to detect infinite update loops
+static mut NESTED_UPDATE_COUNT: u32 = 0;
+static mut ROOT_WITH_NESTED_UPDATES: Option<Rc<RefCell<FiberRootNode>>> = None;
+
+static NESTED_UPDATE_LIMIT: u32 = 50;
+
+// The retries of suspended content are committed at most once per FALLBACK_THROTTLE_MS
+// after a fallback was shown, so that boundaries resolving close together are revealed at once
+static FALLBACK_THROTTLE_MS: f64 = 300.0;
+static mut GLOBAL_MOST_RECENT_FALLBACK_TIME: f64 = 0.0;
 
 static ROOT_INCOMPLETE: u8 = 1;
 static ROOT_COMPLETED: u8 = 2;
 
-pub fn schedule_update_on_fiber(fiber: Rc<RefCell<FiberNode>>, lane: Lane) {
+static NOT_SUSPENDED: u8 = 0;
+static SUSPENDED_ON_DATA: u8 = 1;
+static SUSPENDED_ON_ERROR: u8 = 2;
+
+pub fn schedule_update_on_fiber(
+    fiber: Rc<RefCell<FiberNode>>,
+    lane: Lane,
+    transition: Option<JsValue>,
+) {
     if is_dev() {
         log!("schedule_update_on_fiber, {:?} {:?}", fiber, lane);
     }
@@ -57,10 +93,96 @@ pub fn schedule_update_on_fiber(fiber: Rc<RefCell<FiberNode>>, lane: Lane) {
     if root.is_none() {
         return;
     }
-    root.as_ref().unwrap().borrow_mut().mark_root_updated(lane);
+    if is_interleaved_update() {
+        unsafe {
+            WORK_IN_PROGRESS_ROOT_INTERLEAVED_UPDATED_LANES = merge_lanes(
+                WORK_IN_PROGRESS_ROOT_INTERLEAVED_UPDATED_LANES.clone(),
+                lane.clone(),
+            );
+        }
+    }
+    root.as_ref()
+        .unwrap()
+        .borrow_mut()
+        .mark_root_updated(lane, transition);
     ensure_root_is_scheduled(root.unwrap())
 }
 
+// The retries of suspended content are throttled from the commit of the last fallback
+pub fn mark_commit_time_of_fallback() {
+    unsafe { GLOBAL_MOST_RECENT_FALLBACK_TIME = unstable_now() }
+}
+
+// An update scheduled between the slices of a concurrent render
+pub fn is_interleaved_update() -> bool {
+    unsafe {
+        WORK_IN_PROGRESS_ROOT.is_some()
+            && !EXECUTION_CONTEXT.contains(ExecutionContext::RenderContext)
+    }
+}
+
+// The cache is kept on the root being rendered, so a render that is retried after it suspended
+// reads the same entries. Outside of a render nothing is cached.
+pub fn request_cache_from_pool() -> Map {
+    match unsafe { WORK_IN_PROGRESS_ROOT.clone() } {
+        Some(root) => root
+            .borrow_mut()
+            .pooled_cache
+            .get_or_insert_with(Map::new)
+            .clone(),
+        None => Map::new(),
+    }
+}
+
+// The deferred values of a render catch up together in one transition render, and a component
+// rendered twice (e.g. by StrictMode) doesn't spawn a second one
+pub fn request_deferred_lane() -> Lane {
+    unsafe {
+        if WORK_IN_PROGRESS_DEFERRED_LANE == Lane::NoLane {
+            WORK_IN_PROGRESS_DEFERRED_LANE = claim_next_transition_lane();
+        }
+        WORK_IN_PROGRESS_DEFERRED_LANE.clone()
+    }
+}
+
+pub fn is_already_rendering() -> bool {
+    unsafe {
+        EXECUTION_CONTEXT.clone()
+            & (ExecutionContext::RenderContext | ExecutionContext::CommitContext)
+            != ExecutionContext::NoContext
+    }
+}
+
+pub fn flush_sync(f: &Function) {
+    // Flushing here would start a new render while the work loop is still running,
+    // so the updates are only scheduled and flushed after the current work is done
+    if is_already_rendering() {
+        error!(
+            "flushSync was called from inside a lifecycle method. React cannot flush when React \
+            is already rendering. Consider moving this call to a scheduler task or micro task."
+        );
+        unstable_run_with_priority(Priority::ImmediatePriority, f);
+        return;
+    }
+
+    unstable_run_with_priority(Priority::ImmediatePriority, f);
+    flush_sync_callbacks_until_idle();
+}
+
+// The sync updates scheduled by f are flushed together once the outermost batch is left
+pub fn batched_updates<A, R>(f: impl FnOnce(A) -> R, a: A) -> R {
+    let prev_execution_context = unsafe { EXECUTION_CONTEXT.clone() };
+    unsafe { EXECUTION_CONTEXT |= ExecutionContext::BatchedContext };
+    let result = f(a);
+    unsafe {
+        EXECUTION_CONTEXT = prev_execution_context;
+        if EXECUTION_CONTEXT == ExecutionContext::NoContext {
+            flush_sync_callbacks();
+        }
+    }
+    result
+}
+
 pub fn mark_update_lane_from_fiber_to_root(
     fiber: Rc<RefCell<FiberNode>>,
     lane: Lane,
@@ -103,8 +225,13 @@ pub fn mark_update_lane_from_fiber_to_root(
     None
 }
 
-fn ensure_root_is_scheduled(root: Rc<RefCell<FiberRootNode>>) {
+// SyncLane work is flushed in a micro task from the sync queue, the other lanes are rendered in
+// a scheduler task with the priority of their highest lane
+pub fn ensure_root_is_scheduled(root: Rc<RefCell<FiberRootNode>>) {
     let root_cloned = root.clone();
+    // Lanes that have been starved by higher priority work are rendered synchronously
+    root.borrow_mut()
+        .mark_starved_lanes_as_expired(unstable_now());
     let update_lanes = root_cloned.borrow().get_next_lanes();
     let existing_callback = root_cloned.borrow().callback_node.clone();
     if update_lanes == Lane::NoLane {
@@ -116,7 +243,11 @@ fn ensure_root_is_scheduled(root: Rc<RefCell<FiberRootNode>>) {
         return;
     }
 
-    let cur_priority = get_highest_priority(update_lanes.clone());
+    let cur_priority = if root.borrow().includes_expired_lane(update_lanes.clone()) {
+        Lane::SyncLane
+    } else {
+        get_highest_priority(update_lanes.clone())
+    };
     let prev_priority = root.borrow().callback_priority.clone();
 
     if cur_priority == prev_priority {
@@ -180,9 +311,33 @@ fn render_root(root: Rc<RefCell<FiberRootNode>>, lanes: Lane, should_time_slice:
         EXECUTION_CONTEXT |= ExecutionContext::RenderContext;
     }
 
-    prepare_fresh_stack(root.clone(), lanes.clone());
+    let is_same_render = unsafe {
+        match WORK_IN_PROGRESS_ROOT.as_ref() {
+            Some(wip_root) => {
+                Rc::ptr_eq(wip_root, &root) && WORK_IN_PROGRESS_ROOT_RENDER_LANE == lanes
+            }
+            None => false,
+        }
+    };
+    // Continue the yielded render unless it's been interrupted by another one
+    if !is_same_render || unsafe { WORK_IN_PROGRESS.is_none() } {
+        prepare_fresh_stack(root.clone(), lanes.clone());
+    }
 
     loop {
+        unsafe {
+            if WORK_IN_PROGRESS_SUSPENDED_REASON != NOT_SUSPENDED && WORK_IN_PROGRESS.is_some() {
+                let thrown_value = WORK_IN_PROGRESS_THROWN_VALUE.take().unwrap();
+                WORK_IN_PROGRESS_SUSPENDED_REASON = NOT_SUSPENDED;
+                throw_and_unwind_work_loop(
+                    root.clone(),
+                    WORK_IN_PROGRESS.clone().unwrap(),
+                    thrown_value,
+                    lanes.clone(),
+                );
+            }
+        }
+
         match if should_time_slice {
             work_loop_concurrent()
         } else {
@@ -191,10 +346,7 @@ fn render_root(root: Rc<RefCell<FiberRootNode>>, lanes: Lane, should_time_slice:
             Ok(_) => {
                 break;
             }
-            Err(e) => unsafe {
-                log!("work_loop error {:?}", e);
-                WORK_IN_PROGRESS = None
-            },
+            Err(e) => handle_throw(e),
         };
     }
 
@@ -204,12 +356,14 @@ fn render_root(root: Rc<RefCell<FiberRootNode>>, lanes: Lane, should_time_slice:
 
     unsafe {
         EXECUTION_CONTEXT = prev_execution_context;
-        WORK_IN_PROGRESS_ROOT_RENDER_LANE = Lane::NoLane;
 
         if should_time_slice && WORK_IN_PROGRESS.is_some() {
             return ROOT_INCOMPLETE;
         }
 
+        WORK_IN_PROGRESS_ROOT = None;
+        WORK_IN_PROGRESS_ROOT_RENDER_LANE = Lane::NoLane;
+
         if !should_time_slice && WORK_IN_PROGRESS.is_some() {
             log!("The WIP is not null when render finishing")
         }
@@ -247,12 +401,18 @@ fn perform_concurrent_work_on_root(root: Rc<RefCell<FiberRootNode>>, did_timeout
         return JsValue::undefined();
     }
 
-    let should_time_slice = !did_timeout;
+    let should_time_slice = !did_timeout && !root.borrow().includes_expired_lane(lanes.clone());
     let exit_status = render_root(root.clone(), lanes.clone(), should_time_slice);
 
     ensure_root_is_scheduled(root.clone());
     if exit_status == ROOT_INCOMPLETE {
-        if root.borrow().callback_node.as_ref().unwrap().id != cur_callback_node.unwrap().id {
+        let is_same_callback = match (root.borrow().callback_node.as_ref(), cur_callback_node) {
+            (Some(callback_node), Some(cur_callback_node)) => {
+                callback_node.id == cur_callback_node.id
+            }
+            _ => false,
+        };
+        if !is_same_callback {
             // 调度了更高优更新，这个更新已经被取消了
             return JsValue::undefined();
         }
@@ -277,9 +437,35 @@ fn perform_concurrent_work_on_root(root: Rc<RefCell<FiberRootNode>>, did_timeout
                 .clone()
         };
         root.clone().borrow_mut().finished_work = finished_work;
-        root.clone().borrow_mut().finished_lanes = lanes;
+        root.clone().borrow_mut().finished_lanes = lanes.clone();
+
+        if root
+            .borrow()
+            .includes_only_pinged_transitions(lanes.clone())
+        {
+            let ms_until_timeout =
+                unsafe { GLOBAL_MOST_RECENT_FALLBACK_TIME } + FALLBACK_THROTTLE_MS - unstable_now();
+            if ms_until_timeout > 10.0 {
+                root.borrow_mut().mark_root_suspended(lanes.clone());
+                schedule_throttled_commit(root.clone(), ms_until_timeout);
+                // This task is done, a later ping has to schedule a new render
+                let is_same_callback =
+                    match (root.borrow().callback_node.as_ref(), cur_callback_node) {
+                        (Some(callback_node), Some(cur_callback_node)) => {
+                            callback_node.id == cur_callback_node.id
+                        }
+                        _ => false,
+                    };
+                if is_same_callback {
+                    root.borrow_mut().callback_node = None;
+                    root.borrow_mut().callback_priority = Lane::NoLane;
+                }
+                return JsValue::undefined();
+            }
+        }
 
-        commit_root(root);
+        commit_root(root.clone());
+        flush_layout_updates(root);
     } else {
         todo!("Unsupported status of concurrent render")
     }
@@ -287,36 +473,87 @@ fn perform_concurrent_work_on_root(root: Rc<RefCell<FiberRootNode>>, did_timeout
     JsValue::undefined()
 }
 
+fn schedule_throttled_commit(root: Rc<RefCell<FiberRootNode>>, ms: f64) {
+    let root_cloned = root.clone();
+    let closure = Closure::wrap(Box::new(move |_: JsValue| {
+        root_cloned.borrow_mut().timeout_handle = None;
+        commit_root(root_cloned.clone());
+        flush_layout_updates(root_cloned.clone());
+        JsValue::undefined()
+    }) as Box<dyn Fn(JsValue) -> JsValue>);
+    let function = closure.as_ref().unchecked_ref::<Function>().clone();
+    closure.forget();
+    root.borrow_mut().timeout_handle = Some(unstable_schedule_callback(
+        Priority::NormalPriority,
+        function,
+        ms,
+    ));
+}
+
 fn perform_sync_work_on_root(root: Rc<RefCell<FiberRootNode>>, lanes: Lane) {
-    let next_lane = get_highest_priority(root.borrow().pending_lanes.clone());
+    let mut lanes = lanes;
+    loop {
+        // The passive effects are scheduled with NormalPriority after the commit, a sync render
+        // (e.g. flushSync) runs the pending ones first
+        flush_passive_effects(root.borrow().pending_passive_effects.clone());
 
-    if next_lane != Lane::SyncLane {
-        ensure_root_is_scheduled(root.clone());
-        return;
-    }
+        let next_lane = get_highest_priority(root.borrow().pending_lanes.clone());
 
-    let exit_status = render_root(root.clone(), lanes.clone(), false);
+        if next_lane != Lane::SyncLane && !root.borrow().includes_expired_lane(lanes.clone()) {
+            ensure_root_is_scheduled(root.clone());
+            return;
+        }
 
-    if exit_status == ROOT_COMPLETED {
-        let finished_work = {
-            root.clone()
-                .borrow()
-                .current
-                .clone()
-                .borrow()
-                .alternate
-                .clone()
-        };
-        root.clone().borrow_mut().finished_work = finished_work;
-        root.clone().borrow_mut().finished_lanes = lanes;
+        let exit_status = render_root(root.clone(), lanes.clone(), false);
+
+        if exit_status == ROOT_COMPLETED {
+            let finished_work = {
+                root.clone()
+                    .borrow()
+                    .current
+                    .clone()
+                    .borrow()
+                    .alternate
+                    .clone()
+            };
+            root.clone().borrow_mut().finished_work = finished_work;
+            root.clone().borrow_mut().finished_lanes = lanes;
+
+            commit_root(root.clone());
+        } else {
+            todo!("Unsupported status of sync render")
+        }
 
-        commit_root(root);
-    } else {
-        todo!("Unsupported status of sync render")
+        // The sync updates scheduled in the layout phase are rendered in the next iteration,
+        // the sync callback scheduled for them then finds no sync work left and does nothing
+        if !include_some_lanes(root.borrow().pending_lanes.clone(), Lane::SyncLane) {
+            return;
+        }
+        lanes = Lane::SyncLane;
+    }
+}
+
+// The sync updates scheduled in the layout phase of a concurrent commit are flushed before
+// the browser paints
+fn flush_layout_updates(root: Rc<RefCell<FiberRootNode>>) {
+    if include_some_lanes(root.borrow().pending_lanes.clone(), Lane::SyncLane) {
+        perform_sync_work_on_root(root, Lane::SyncLane);
+    }
+}
+
+// Flushes the passive effects of the root that was committed last, if they haven't run yet
+pub fn flush_pending_passive_effects() -> bool {
+    let root = unsafe { ROOT_WITH_PENDING_PASSIVE_EFFECTS.take() };
+    match root {
+        Some(root) => {
+            let pending_passive_effects = { root.borrow().pending_passive_effects.clone() };
+            flush_passive_effects(pending_passive_effects)
+        }
+        None => false,
     }
 }
 
-fn flush_passive_effects(pending_passive_effects: Rc<RefCell<PendingPassiveEffects>>) -> bool {
+pub fn flush_passive_effects(pending_passive_effects: Rc<RefCell<PendingPassiveEffects>>) -> bool {
     unsafe {
         if EXECUTION_CONTEXT
             .contains(ExecutionContext::RenderContext | ExecutionContext::CommitContext)
@@ -352,13 +589,36 @@ fn commit_root(root: Rc<RefCell<FiberRootNode>>) {
     }
     let lanes = root.borrow().finished_lanes.clone();
 
+    // The passive effects of an earlier commit run before the effects of this one are collected
+    flush_pending_passive_effects();
+
     let finished_work = cloned.borrow().finished_work.clone().unwrap();
     cloned.borrow_mut().finished_work = None;
     cloned.borrow_mut().finished_lanes = Lane::NoLane;
+    cloned.borrow_mut().pooled_cache = None;
     cloned.borrow_mut().callback_node = None;
     cloned.borrow_mut().callback_priority = Lane::NoLane;
 
-    cloned.borrow_mut().mark_root_finished(lanes.clone());
+    let remaining_lanes = {
+        let finished_work = finished_work.borrow();
+        merge_lanes(
+            finished_work.lanes.clone(),
+            finished_work.child_lanes.clone(),
+        )
+    };
+    cloned.borrow_mut().mark_root_finished(remaining_lanes);
+    // The interleaved updates haven't been rendered yet, keep their lanes pending
+    let interleaved_lanes = unsafe {
+        std::mem::replace(
+            &mut WORK_IN_PROGRESS_ROOT_INTERLEAVED_UPDATED_LANES,
+            Lane::NoLane,
+        )
+    };
+    if interleaved_lanes != Lane::NoLane {
+        cloned
+            .borrow_mut()
+            .mark_root_updated(interleaved_lanes, None);
+    }
 
     if lanes == Lane::NoLane {
         log!("Commit phase finished lane should not be NoLane")
@@ -368,24 +628,21 @@ fn commit_root(root: Rc<RefCell<FiberRootNode>>) {
     let flags = finished_work.borrow().flags.clone();
 
     // useEffect
-    let root_cloned = root.clone();
     let passive_mask = get_passive_mask();
-    if flags.clone() & passive_mask.clone() != Flags::NoFlags
-        || subtree_flags.clone() & passive_mask != Flags::NoFlags
-    {
-        if unsafe { !ROOT_DOES_HAVE_PASSIVE_EFFECTS } {
-            unsafe { ROOT_DOES_HAVE_PASSIVE_EFFECTS = true }
-            let closure = Closure::wrap(Box::new(move || {
-                flush_passive_effects(root_cloned.borrow().pending_passive_effects.clone());
-            }) as Box<dyn Fn()>);
-            let function = closure.as_ref().unchecked_ref::<Function>().clone();
-            closure.forget();
-            unstable_schedule_callback_no_delay(Priority::NormalPriority, function);
-        }
+    let root_has_passive_effects = flags.clone() & passive_mask.clone() != Flags::NoFlags
+        || subtree_flags.clone() & passive_mask != Flags::NoFlags;
+    if root_has_passive_effects {
+        let closure = Closure::wrap(Box::new(move || {
+            flush_pending_passive_effects();
+        }) as Box<dyn Fn()>);
+        let function = closure.as_ref().unchecked_ref::<Function>().clone();
+        closure.forget();
+        unstable_schedule_callback_no_delay(Priority::NormalPriority, function);
     }
 
-    let subtree_has_effect = get_mutation_mask().contains(subtree_flags);
-    let root_has_effect = get_mutation_mask().contains(flags);
+    let effect_mask = get_mutation_mask() | get_passive_mask() | Flags::LayoutMask;
+    let subtree_has_effect = subtree_flags.intersects(effect_mask.clone());
+    let root_has_effect = flags.intersects(effect_mask);
 
     if subtree_has_effect || root_has_effect {
         let prev_execution_context: ExecutionContext;
@@ -399,13 +656,26 @@ fn commit_root(root: Rc<RefCell<FiberRootNode>>) {
         // 1/3: Before Mutation
 
         // 2/3: Mutation
+        let host_config = unsafe { HOST_CONFIG.as_ref().unwrap().clone() };
+        let container = { root.borrow().container.clone() };
+        host_config.prepare_for_commit(container.clone());
         commit_mutation_effects(finished_work.clone(), root.clone());
+        host_config.reset_after_commit(container);
 
         // Switch Fiber Tree
         cloned.borrow_mut().current = finished_work.clone();
 
         // 3/3: Layout
-        commit_layout_effects(finished_work.clone(), root.clone());
+        // Updates scheduled in the layout phase are sync, they are flushed before the browser paints
+        let finished_work_cloned = finished_work.clone();
+        let root_cloned = root.clone();
+        let layout_closure = Closure::wrap(Box::new(move || {
+            commit_layout_effects(finished_work_cloned.clone(), root_cloned.clone());
+        }) as Box<dyn Fn()>);
+        unstable_run_with_priority(
+            Priority::ImmediatePriority,
+            layout_closure.as_ref().unchecked_ref::<Function>(),
+        );
 
         unsafe {
             EXECUTION_CONTEXT = prev_execution_context;
@@ -414,21 +684,169 @@ fn commit_root(root: Rc<RefCell<FiberRootNode>>) {
         cloned.borrow_mut().current = finished_work.clone();
     }
 
-    unsafe {
-        ROOT_DOES_HAVE_PASSIVE_EFFECTS = false;
+    if root_has_passive_effects {
+        unsafe { ROOT_WITH_PENDING_PASSIVE_EFFECTS = Some(root.clone()) };
+    }
+    ensure_root_is_scheduled(root.clone());
+
+    let remaining_lanes = root.borrow().pending_lanes.clone();
+    if include_some_lanes(remaining_lanes, Lane::SyncLane) {
+        unsafe {
+            let is_same_root = match ROOT_WITH_NESTED_UPDATES.as_ref() {
+                Some(nested_root) => Rc::ptr_eq(nested_root, &root),
+                None => false,
+            };
+            if is_same_root {
+                NESTED_UPDATE_COUNT += 1;
+            } else {
+                NESTED_UPDATE_COUNT = 0;
+                ROOT_WITH_NESTED_UPDATES = Some(root.clone());
+            }
+            if NESTED_UPDATE_COUNT > NESTED_UPDATE_LIMIT {
+                NESTED_UPDATE_COUNT = 0;
+                ROOT_WITH_NESTED_UPDATES = None;
+                // Drop the sync work from the root and its fibers, so the loop ends here and
+                // isn't started over by the next sync update of the root
+                root.borrow_mut().pending_lanes -= Lane::SyncLane;
+                let current = { root.borrow().current.clone() };
+                remove_lane_from_fibers(current, Lane::SyncLane);
+                ensure_root_is_scheduled(root.clone());
+                error!(
+                    "Maximum update depth exceeded. This can happen when a component repeatedly \
+                    calls setState inside a ref callback or a render callback. React limits the \
+                    number of nested updates to prevent infinite loops."
+                );
+            }
+        }
+    } else {
+        unsafe {
+            NESTED_UPDATE_COUNT = 0;
+            ROOT_WITH_NESTED_UPDATES = None;
+        }
+    }
+}
+
+// The updates stay in the hook queues, they are only rendered with the next update of their fiber
+fn remove_lane_from_fibers(fiber: Rc<RefCell<FiberNode>>, lane: Lane) {
+    let mut node = Some(fiber);
+    while let Some(fiber) = node {
+        let child = {
+            let mut fiber = fiber.borrow_mut();
+            let subtree_has_lane = include_some_lanes(fiber.child_lanes.clone(), lane.clone());
+            fiber.lanes -= lane.clone();
+            fiber.child_lanes -= lane.clone();
+            if let Some(alternate) = fiber.alternate.as_ref() {
+                alternate.borrow_mut().lanes -= lane.clone();
+                alternate.borrow_mut().child_lanes -= lane.clone();
+            }
+            if subtree_has_lane {
+                fiber.child.clone()
+            } else {
+                None
+            }
+        };
+        if let Some(child) = child {
+            remove_lane_from_fibers(child, lane.clone());
+        }
+        node = fiber.borrow().sibling.clone();
     }
-    ensure_root_is_scheduled(root);
 }
 
 fn prepare_fresh_stack(root: Rc<RefCell<FiberRootNode>>, lane: Lane) {
     let root = root.clone();
+    // The throttled commit is outdated, the new render includes its work
+    let timeout_handle = { root.borrow_mut().timeout_handle.take() };
+    if let Some(timeout_handle) = timeout_handle {
+        unstable_cancel_callback(timeout_handle);
+        root.borrow_mut().finished_work = None;
+        root.borrow_mut().finished_lanes = Lane::NoLane;
+    }
+    reset_work_in_progress_stack();
+    finish_queueing_interleaved_updates();
     unsafe {
+        WORK_IN_PROGRESS_ROOT = Some(root.clone());
+        WORK_IN_PROGRESS_ROOT_INTERLEAVED_UPDATED_LANES = Lane::NoLane;
+        WORK_IN_PROGRESS_DEFERRED_LANE = Lane::NoLane;
         WORK_IN_PROGRESS = Some(FiberNode::create_work_in_progress(
             root.borrow().current.clone(),
             JsValue::null(),
         ));
         WORK_IN_PROGRESS_ROOT_RENDER_LANE = lane;
+        WORK_IN_PROGRESS_SUSPENDED_REASON = NOT_SUSPENDED;
+        WORK_IN_PROGRESS_THROWN_VALUE = None;
     }
+    reset_suspense_handler_stack();
+}
+
+// The render in progress is abandoned, the ancestors of the current unit of work have pushed
+// context values that would otherwise be read by the next render
+fn reset_work_in_progress_stack() {
+    let mut interrupted_work = unsafe {
+        match WORK_IN_PROGRESS.clone() {
+            // A unit of work that threw has begun, one that is next hasn't
+            Some(wip) if WORK_IN_PROGRESS_SUSPENDED_REASON != NOT_SUSPENDED => Some(wip),
+            Some(wip) => wip.borrow()._return.clone(),
+            None => None,
+        }
+    };
+    while let Some(fiber) = interrupted_work {
+        unwind_interrupted_work(fiber.clone());
+        interrupted_work = fiber.borrow()._return.clone();
+    }
+    unsafe {
+        WORK_IN_PROGRESS = None;
+        WORK_IN_PROGRESS_SUSPENDED_REASON = NOT_SUSPENDED;
+    }
+}
+
+fn handle_throw(thrown_value: JsValue) {
+    // Only thenables caught by a Suspense boundary can be recovered from for now
+    if is_thenable(&thrown_value) && get_suspense_handler().is_some() {
+        unsafe {
+            WORK_IN_PROGRESS_SUSPENDED_REASON = SUSPENDED_ON_DATA;
+            WORK_IN_PROGRESS_THROWN_VALUE = Some(thrown_value);
+        }
+    } else {
+        log!("work_loop error {:?}", thrown_value);
+        // There are no error boundaries, the render is unwound to the root
+        unsafe { WORK_IN_PROGRESS_SUSPENDED_REASON = SUSPENDED_ON_ERROR };
+        reset_work_in_progress_stack();
+    }
+}
+
+fn throw_and_unwind_work_loop(
+    root: Rc<RefCell<FiberRootNode>>,
+    unit_of_work: Rc<RefCell<FiberNode>>,
+    thrown_value: JsValue,
+    lane: Lane,
+) {
+    throw_exception(root, thrown_value, lane);
+    unwind_unit_of_work(unit_of_work);
+}
+
+fn unwind_unit_of_work(unit_of_work: Rc<RefCell<FiberNode>>) {
+    let mut incomplete_work = Some(unit_of_work);
+    while incomplete_work.is_some() {
+        let incomplete = incomplete_work.unwrap();
+        let next = unwind_work(incomplete.clone());
+        if next.is_some() {
+            let next = next.unwrap();
+            next.borrow_mut().flags &= get_host_effect_mask();
+            unsafe { WORK_IN_PROGRESS = Some(next) };
+            return;
+        }
+
+        let return_fiber = incomplete.borrow()._return.clone();
+        if return_fiber.is_some() {
+            // The children of return_fiber will be reconciled again
+            let return_fiber = return_fiber.clone().unwrap();
+            return_fiber.borrow_mut().deletions = vec![];
+            return_fiber.borrow_mut().subtree_flags = Flags::NoFlags;
+        }
+        incomplete_work = return_fiber;
+    }
+
+    unsafe { WORK_IN_PROGRESS = None }
 }
 
 fn work_loop_sync() -> Result<(), JsValue> {
@@ -1,10 +1,19 @@
+// TODO: there are no class components (ClassComponent = 1) and so no error boundaries yet. Once
+// they exist, a class that throws while it mounts becomes an IncompleteClassComponent (17), so the
+// retry from its boundary constructs it again instead of taking the update path.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum WorkTag {
     FunctionComponent = 0,
     HostRoot = 3,
+    HostPortal = 4,
     HostComponent = 5,
     HostText = 6,
     Fragment = 7,
     ContextProvider = 8,
-    MemoComponent = 15,
+    ContextConsumer = 9,
+    Mode = 10,
+    SuspenseComponent = 13,
+    MemoComponent = 14,
+    SimpleMemoComponent = 15,
+    OffscreenComponent = 22,
 }
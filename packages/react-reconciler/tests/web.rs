@@ -0,0 +1,148 @@
+//! Test suite for the Web and headless browsers.
+
+#![cfg(target_arch = "wasm32")]
+
+extern crate wasm_bindgen_test;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::JsValue;
+use web_sys::js_sys::{Object, Reflect};
+use wasm_bindgen_test::*;
+
+use shared::{REACT_ELEMENT_TYPE, REACT_STRICT_MODE_TYPE};
+
+use react_reconciler::fiber::{FiberNode, FiberRootNode};
+use react_reconciler::fiber_flags::Flags;
+use react_reconciler::fiber_lanes::Lane;
+use react_reconciler::type_of_mode::TypeOfMode;
+use react_reconciler::work_tags::WorkTag;
+
+fn create_fiber(tag: WorkTag) -> Rc<RefCell<FiberNode>> {
+    Rc::new(RefCell::new(FiberNode::new(
+        tag,
+        JsValue::null(),
+        JsValue::null(),
+        JsValue::null(),
+    )))
+}
+
+#[wasm_bindgen_test]
+fn test_reused_alternate_starts_without_effects() {
+    let current = create_fiber(WorkTag::HostComponent);
+    let wip = FiberNode::create_work_in_progress(current.clone(), JsValue::null());
+    {
+        let mut wip = wip.borrow_mut();
+        wip.flags |= Flags::Placement | Flags::Update;
+        wip.subtree_flags |= Flags::Placement | Flags::ChildDeletion;
+        wip.deletions.push(create_fiber(WorkTag::HostText));
+    }
+
+    let reused = FiberNode::create_work_in_progress(current, JsValue::null());
+    assert!(Rc::ptr_eq(&reused, &wip));
+    let reused = reused.borrow();
+    assert!(reused.flags.is_empty());
+    assert!(reused.subtree_flags.is_empty());
+    assert!(reused.deletions.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_new_alternate_does_not_copy_effects_of_current() {
+    let current = create_fiber(WorkTag::HostComponent);
+    current.borrow_mut().flags |= Flags::Placement;
+
+    let wip = FiberNode::create_work_in_progress(current, JsValue::null());
+    assert!(wip.borrow().flags.is_empty());
+}
+
+#[wasm_bindgen_test]
+fn test_root_lanes_lifecycle() {
+    let mut root = FiberRootNode::new(Rc::new(()), create_fiber(WorkTag::HostRoot));
+
+    root.mark_root_updated(Lane::TransitionLane1, None);
+    assert_eq!(root.pending_lanes, Lane::TransitionLane1);
+    assert_eq!(root.get_next_lanes(), Lane::TransitionLane1);
+
+    root.mark_root_suspended(Lane::TransitionLane1);
+    assert_eq!(root.suspended_lanes, Lane::TransitionLane1);
+    assert_eq!(root.get_next_lanes(), Lane::NoLane);
+
+    root.mark_root_pinged(Lane::TransitionLane1);
+    assert_eq!(root.pinged_lanes, Lane::TransitionLane1);
+    assert_eq!(root.get_next_lanes(), Lane::TransitionLane1);
+
+    root.mark_root_finished(Lane::NoLane);
+    assert_eq!(root.pending_lanes, Lane::NoLane);
+    assert_eq!(root.suspended_lanes, Lane::NoLane);
+    assert_eq!(root.pinged_lanes, Lane::NoLane);
+}
+
+#[wasm_bindgen_test]
+fn test_root_update_unblocks_suspended_lanes() {
+    let mut root = FiberRootNode::new(Rc::new(()), create_fiber(WorkTag::HostRoot));
+    root.mark_root_updated(Lane::TransitionLane1, None);
+    root.mark_root_suspended(Lane::TransitionLane1);
+    // a ping of a lane that isn't suspended is ignored
+    root.mark_root_pinged(Lane::DefaultLane);
+    assert_eq!(root.pinged_lanes, Lane::NoLane);
+
+    root.mark_root_updated(Lane::DefaultLane, None);
+    assert_eq!(root.suspended_lanes, Lane::NoLane);
+    assert_eq!(root.get_next_lanes(), Lane::DefaultLane);
+}
+
+#[wasm_bindgen_test]
+fn test_lanes_of_deleted_fibers_are_no_longer_pending() {
+    let mut root = FiberRootNode::new(Rc::new(()), create_fiber(WorkTag::HostRoot));
+    // a sync update deletes the fiber that has a pending transition update
+    root.mark_root_updated(Lane::TransitionLane1, None);
+    root.mark_root_updated(Lane::SyncLane, None);
+    assert_eq!(root.get_next_lanes(), Lane::SyncLane);
+
+    // the finished tree has no lanes left
+    root.mark_root_finished(Lane::NoLane);
+    assert_eq!(root.pending_lanes, Lane::NoLane);
+    assert_eq!(root.get_next_lanes(), Lane::NoLane);
+}
+
+#[wasm_bindgen_test]
+fn test_remaining_lanes_stay_pending() {
+    let mut root = FiberRootNode::new(Rc::new(()), create_fiber(WorkTag::HostRoot));
+    root.mark_root_updated(Lane::TransitionLane1, None);
+    root.mark_root_updated(Lane::SyncLane, None);
+
+    root.mark_root_finished(Lane::TransitionLane1);
+    assert_eq!(root.pending_lanes, Lane::TransitionLane1);
+    assert_eq!(root.get_next_lanes(), Lane::TransitionLane1);
+}
+
+fn create_element(_type: &JsValue) -> JsValue {
+    let element = Object::new();
+    Reflect::set(&element, &"$$typeof".into(), &REACT_ELEMENT_TYPE.into()).unwrap();
+    Reflect::set(&element, &"type".into(), _type).unwrap();
+    Reflect::set(&element, &"key".into(), &JsValue::null()).unwrap();
+    Reflect::set(&element, &"ref".into(), &JsValue::null()).unwrap();
+    Reflect::set(&element, &"props".into(), &Object::new()).unwrap();
+    element.into()
+}
+
+#[wasm_bindgen_test]
+fn test_strict_mode_subtree_inherits_the_strict_mode_bit() {
+    let root_mode = TypeOfMode::ConcurrentMode;
+    let strict_mode = FiberNode::create_fiber_from_element(
+        &create_element(&REACT_STRICT_MODE_TYPE.into()),
+        root_mode.clone(),
+    );
+    assert_eq!(strict_mode.tag, WorkTag::Mode);
+    assert!(strict_mode.mode.contains(TypeOfMode::StrictLegacyMode));
+    assert!(strict_mode.mode.contains(TypeOfMode::ConcurrentMode));
+
+    let inside =
+        FiberNode::create_fiber_from_element(&create_element(&"div".into()), strict_mode.mode);
+    assert!(inside.mode.contains(TypeOfMode::StrictLegacyMode));
+
+    let outside = FiberNode::create_fiber_from_element(&create_element(&"div".into()), root_mode);
+    assert!(!outside.mode.contains(TypeOfMode::StrictLegacyMode));
+    assert_eq!(outside.mode, TypeOfMode::ConcurrentMode);
+}
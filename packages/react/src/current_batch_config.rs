@@ -0,0 +1,65 @@
+use js_sys::{Function, Object};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsValue;
+
+use shared::derive_from_js_value;
+
+pub struct CurrentBatchConfig {
+    // The transition object of the startTransition scope being executed
+    pub transition: Option<JsValue>,
+    // The transition of the async actions that haven't settled yet, the transitions started in
+    // the meantime reuse it so their updates are entangled with the action
+    pub async_action: Option<JsValue>,
+    pub pending_async_actions: u32,
+}
+
+pub static mut CURRENT_BATCH_CONFIG: CurrentBatchConfig = CurrentBatchConfig {
+    transition: None,
+    async_action: None,
+    pending_async_actions: 0,
+};
+
+#[wasm_bindgen(js_name = startTransition)]
+pub unsafe fn start_transition(scope: &Function) -> Result<(), JsValue> {
+    let prev_transition = CURRENT_BATCH_CONFIG.transition.take();
+    let transition = match CURRENT_BATCH_CONFIG.async_action.as_ref() {
+        Some(async_action) => async_action.clone(),
+        None => Object::new().into(),
+    };
+    CURRENT_BATCH_CONFIG.transition = Some(transition.clone());
+    let result = scope.call0(&JsValue::null());
+    CURRENT_BATCH_CONFIG.transition = prev_transition;
+    let result = result?;
+    // An async function is an action, the transition lasts until its promise settles
+    if result.is_object() && derive_from_js_value(&result, "then").is_function() {
+        entangle_async_action(transition, &result)?;
+    }
+    Ok(())
+}
+
+unsafe fn entangle_async_action(transition: JsValue, thenable: &JsValue) -> Result<(), JsValue> {
+    CURRENT_BATCH_CONFIG.async_action = Some(transition);
+    CURRENT_BATCH_CONFIG.pending_async_actions += 1;
+    let on_settled = Closure::wrap(Box::new(move |_: JsValue| {
+        CURRENT_BATCH_CONFIG.pending_async_actions -= 1;
+        if CURRENT_BATCH_CONFIG.pending_async_actions == 0 {
+            CURRENT_BATCH_CONFIG.async_action = None;
+        }
+    }) as Box<dyn FnMut(JsValue)>);
+    let then = derive_from_js_value(thenable, "then");
+    then.dyn_ref::<Function>().unwrap().call2(
+        thenable,
+        on_settled.as_ref().unchecked_ref::<Function>(),
+        on_settled.as_ref().unchecked_ref::<Function>(),
+    )?;
+    on_settled.forget();
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = getCurrentTransition)]
+pub unsafe fn get_current_transition() -> JsValue {
+    match CURRENT_BATCH_CONFIG.transition.as_ref() {
+        Some(transition) => transition.clone(),
+        None => JsValue::null(),
+    }
+}
@@ -10,6 +10,11 @@ pub struct Dispatcher {
     pub use_memo: Function,
     pub use_callback: Function,
     pub use_context: Function,
+    pub use_deferred_value: Function,
+    pub use_id: Function,
+    pub use_transition: Function,
+    pub get_cache_for_type: Function,
+    pub use_: Function,
 }
 
 unsafe impl Send for Dispatcher {}
@@ -22,6 +27,11 @@ impl Dispatcher {
         use_memo: Function,
         use_callback: Function,
         use_context: Function,
+        use_deferred_value: Function,
+        use_id: Function,
+        use_transition: Function,
+        get_cache_for_type: Function,
+        use_: Function,
     ) -> Self {
         Dispatcher {
             use_state,
@@ -30,6 +40,11 @@ impl Dispatcher {
             use_memo,
             use_callback,
             use_context,
+            use_deferred_value,
+            use_id,
+            use_transition,
+            get_cache_for_type,
+            use_,
         }
     }
 }
@@ -55,6 +70,11 @@ pub unsafe fn update_dispatcher(args: &JsValue) {
     let use_memo = derive_function_from_js_value(args, "use_memo");
     let use_callback = derive_function_from_js_value(args, "use_callback");
     let use_context = derive_function_from_js_value(args, "use_context");
+    let use_deferred_value = derive_function_from_js_value(args, "use_deferred_value");
+    let use_id = derive_function_from_js_value(args, "use_id");
+    let use_transition = derive_function_from_js_value(args, "use_transition");
+    let get_cache_for_type = derive_function_from_js_value(args, "get_cache_for_type");
+    let use_ = derive_function_from_js_value(args, "use");
     CURRENT_DISPATCHER.current = Some(Box::new(Dispatcher::new(
         use_state,
         use_effect,
@@ -62,5 +82,10 @@ pub unsafe fn update_dispatcher(args: &JsValue) {
         use_memo,
         use_callback,
         use_context,
+        use_deferred_value,
+        use_id,
+        use_transition,
+        get_cache_for_type,
+        use_,
     )))
 }
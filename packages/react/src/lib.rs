@@ -8,6 +8,7 @@ use shared::{
 
 use crate::current_dispatcher::CURRENT_DISPATCHER;
 
+pub mod current_batch_config;
 pub mod current_dispatcher;
 
 fn resolve_key(val: &JsValue) -> JsValue {
@@ -120,6 +121,8 @@ pub unsafe fn use_state(initial_state: &JsValue) -> Result<JsValue, JsValue> {
     use_state.call1(&JsValue::null(), initial_state)
 }
 
+// TODO: there is no useLayoutEffect and no server renderer (render_to_string) yet, once both
+// exist the server dispatcher should warn once that useLayoutEffect does nothing on the server
 #[wasm_bindgen(js_name = useEffect)]
 pub unsafe fn use_effect(create: &JsValue, deps: &JsValue) {
     let use_effect = &CURRENT_DISPATCHER.current.as_ref().unwrap().use_effect;
@@ -150,6 +153,51 @@ pub unsafe fn use_context(context: &JsValue) -> Result<JsValue, JsValue> {
     use_context.call1(&JsValue::null(), context)
 }
 
+#[wasm_bindgen(js_name = useDeferredValue)]
+pub unsafe fn use_deferred_value(
+    value: &JsValue,
+    initial_value: &JsValue,
+) -> Result<JsValue, JsValue> {
+    let use_deferred_value = &CURRENT_DISPATCHER
+        .current
+        .as_ref()
+        .unwrap()
+        .use_deferred_value;
+    use_deferred_value.call2(&JsValue::null(), value, initial_value)
+}
+
+#[wasm_bindgen(js_name = useId)]
+pub unsafe fn use_id() -> Result<JsValue, JsValue> {
+    let use_id = &CURRENT_DISPATCHER.current.as_ref().unwrap().use_id;
+    use_id.call0(&JsValue::null())
+}
+
+// Returns [isPending, startTransition], isPending stays true until an async action settles
+#[wasm_bindgen(js_name = useTransition)]
+pub unsafe fn use_transition() -> Result<JsValue, JsValue> {
+    let use_transition = &CURRENT_DISPATCHER.current.as_ref().unwrap().use_transition;
+    use_transition.call0(&JsValue::null())
+}
+
+// Returns the value resource_type() created for the render in progress, e.g. the storage of a
+// cache() that dedupes the requests of a render and its retries
+#[wasm_bindgen(js_name = unstable_getCacheForType)]
+pub unsafe fn get_cache_for_type(resource_type: &JsValue) -> Result<JsValue, JsValue> {
+    let get_cache_for_type = &CURRENT_DISPATCHER
+        .current
+        .as_ref()
+        .unwrap()
+        .get_cache_for_type;
+    get_cache_for_type.call1(&JsValue::null(), resource_type)
+}
+
+// `use` is a keyword, so the Rust name has a trailing underscore
+#[wasm_bindgen(js_name = "use")]
+pub unsafe fn use_(usable: &JsValue) -> Result<JsValue, JsValue> {
+    let use_ = &CURRENT_DISPATCHER.current.as_ref().unwrap().use_;
+    use_.call1(&JsValue::null(), usable)
+}
+
 #[wasm_bindgen(js_name = createContext)]
 pub unsafe fn create_context(default_value: &JsValue) -> JsValue {
     let context = Object::new();
@@ -167,6 +215,7 @@ pub unsafe fn create_context(default_value: &JsValue) -> JsValue {
     );
     Reflect::set(&provider, &"_context".into(), &context);
     Reflect::set(&context, &"Provider".into(), &provider);
+    Reflect::set(&context, &"Consumer".into(), &context);
     context.into()
 }
 
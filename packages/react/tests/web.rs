@@ -4,11 +4,134 @@
 
 extern crate wasm_bindgen_test;
 
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::JsValue;
 use wasm_bindgen_test::*;
 
+use react::{create_context, memo};
+use shared::{
+    get_component_name_from_type, REACT_FORWARD_REF_TYPE, REACT_FRAGMENT_TYPE, REACT_LAZY_TYPE,
+    REACT_PROFILER_TYPE, REACT_STRICT_MODE_TYPE, REACT_SUSPENSE_LIST_TYPE, REACT_SUSPENSE_TYPE,
+};
+
 // wasm_bindgen_test_configure!(run_in_browser);
 
+fn create_type(_typeof: &str, fields: &[(&str, &JsValue)]) -> JsValue {
+    let object = Object::new();
+    Reflect::set(&object, &"$$typeof".into(), &JsValue::from_str(_typeof)).unwrap();
+    for (key, value) in fields {
+        Reflect::set(&object, &JsValue::from_str(key), value).unwrap();
+    }
+    object.into()
+}
+
+fn named_function(name: &str) -> JsValue {
+    Function::new_no_args(&format!("return function {}() {{}}", name))
+        .call0(&JsValue::null())
+        .unwrap()
+}
+
+fn name_of(_type: &JsValue) -> Option<String> {
+    get_component_name_from_type(_type)
+}
+
 #[wasm_bindgen_test]
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn get_name_of_host_and_function_components() {
+    assert_eq!(name_of(&JsValue::from_str("div")), Some("div".to_string()));
+    assert_eq!(name_of(&named_function("App")), Some("App".to_string()));
+
+    let with_display_name = named_function("App");
+    Reflect::set(&with_display_name, &"displayName".into(), &"MyApp".into()).unwrap();
+    assert_eq!(name_of(&with_display_name), Some("MyApp".to_string()));
+    assert_eq!(name_of(&JsValue::null()), None);
+}
+
+#[wasm_bindgen_test]
+fn get_name_of_special_types() {
+    for (_type, name) in [
+        (REACT_FRAGMENT_TYPE, "Fragment"),
+        (REACT_SUSPENSE_TYPE, "Suspense"),
+        (REACT_SUSPENSE_LIST_TYPE, "SuspenseList"),
+        (REACT_PROFILER_TYPE, "Profiler"),
+        (REACT_STRICT_MODE_TYPE, "StrictMode"),
+    ] {
+        assert_eq!(name_of(&JsValue::from_str(_type)), Some(name.to_string()));
+    }
+}
+
+#[wasm_bindgen_test]
+fn get_name_of_context() {
+    let context = unsafe { create_context(&JsValue::null()) };
+    let provider = Reflect::get(&context, &"Provider".into()).unwrap();
+    let consumer = Reflect::get(&context, &"Consumer".into()).unwrap();
+    assert_eq!(name_of(&provider), Some("Context.Provider".to_string()));
+    assert_eq!(name_of(&consumer), Some("Context.Consumer".to_string()));
+
+    Reflect::set(&context, &"displayName".into(), &"Theme".into()).unwrap();
+    assert_eq!(name_of(&provider), Some("Theme.Provider".to_string()));
+    assert_eq!(name_of(&consumer), Some("Theme.Consumer".to_string()));
+}
+
+#[wasm_bindgen_test]
+fn get_name_of_forward_ref() {
+    let render = named_function("Input");
+    let forward_ref = create_type(REACT_FORWARD_REF_TYPE, &[("render", &render)]);
+    assert_eq!(name_of(&forward_ref), Some("ForwardRef(Input)".to_string()));
+
+    let anonymous = create_type(
+        REACT_FORWARD_REF_TYPE,
+        &[("render", &Function::new_no_args("").into())],
+    );
+    assert_eq!(
+        name_of(&anonymous),
+        Some("ForwardRef(anonymous)".to_string())
+    );
+
+    Reflect::set(&forward_ref, &"displayName".into(), &"FancyInput".into()).unwrap();
+    assert_eq!(name_of(&forward_ref), Some("FancyInput".to_string()));
+}
+
+#[wasm_bindgen_test]
+fn get_name_of_memo() {
+    let memoized = memo(&named_function("Item"), &JsValue::undefined());
+    assert_eq!(name_of(&memoized), Some("Item".to_string()));
+
+    let anonymous = memo(&JsValue::undefined(), &JsValue::undefined());
+    assert_eq!(name_of(&anonymous), Some("Memo".to_string()));
+
+    Reflect::set(&memoized, &"displayName".into(), &"MemoItem".into()).unwrap();
+    assert_eq!(name_of(&memoized), Some("MemoItem".to_string()));
+}
+
+#[wasm_bindgen_test]
+fn get_name_of_lazy() {
+    let module = Object::new();
+    Reflect::set(&module, &"default".into(), &named_function("Page")).unwrap();
+    let payload = Object::new();
+    Reflect::set(&payload, &"_status".into(), &JsValue::from_f64(1.0)).unwrap();
+    Reflect::set(&payload, &"_result".into(), &module).unwrap();
+    // init would start loading the component, it must not be called
+    let init = Function::new_no_args("throw new Error('init was called')");
+    let lazy = create_type(
+        REACT_LAZY_TYPE,
+        &[
+            ("_payload", &payload.into()),
+            ("_init", &init.clone().into()),
+        ],
+    );
+    assert_eq!(name_of(&lazy), Some("Page".to_string()));
+
+    // not resolved yet
+    let payload = Object::new();
+    Reflect::set(&payload, &"_status".into(), &JsValue::from_f64(-1.0)).unwrap();
+    let pending = create_type(
+        REACT_LAZY_TYPE,
+        &[("_payload", &payload.into()), ("_init", &init.into())],
+    );
+    assert_eq!(name_of(&pending), None);
+}
@@ -2,7 +2,7 @@ use std::cmp::{Ordering, PartialEq};
 
 use shared::log;
 use wasm_bindgen::prelude::*;
-use web_sys::js_sys::{global, Function};
+use web_sys::js_sys::{global, Function, Reflect};
 use web_sys::{MessageChannel, MessagePort};
 
 use crate::heap::{peek, peek_mut, pop, push};
@@ -22,6 +22,8 @@ static mut IS_MESSAGE_LOOP_RUNNING: bool = false;
 static mut MESSAGE_CHANNEL: Option<MessageChannel> = None;
 // static mut MESSAGE_CHANNEL_LISTENED: bool = false;
 static mut START_TIME: f64 = -1.0;
+// Set by requestPaint, the work loop yields to let the browser paint
+static mut NEEDS_PAINT: bool = false;
 static mut CURRENT_PRIORITY_LEVEL: Priority = Priority::NormalPriority;
 static mut CURRENT_TASK: Option<&Task> = None;
 static mut PORT1: Option<MessagePort> = None;
@@ -57,6 +59,7 @@ extern "C" {
     fn hasSetImmediate(this: &Global) -> JsValue;
 }
 
+#[wasm_bindgen]
 #[derive(Clone, Debug)]
 pub struct Task {
     pub id: u32,
@@ -83,7 +86,7 @@ impl Task {
                 expiration_time,
                 sort_index: -1.0,
             };
-            TASK_ID_COUNTER += TASK_ID_COUNTER;
+            TASK_ID_COUNTER += 1;
             s
         }
     }
@@ -126,7 +129,7 @@ impl Ord for Task {
     }
 }
 
-fn unstable_now() -> f64 {
+pub fn unstable_now() -> f64 {
     Performance::now().unwrap_or_else(|_| date_now())
 }
 
@@ -192,6 +195,7 @@ fn perform_work_until_deadline() {
             let current_time = unstable_now();
 
             START_TIME = current_time;
+            NEEDS_PAINT = false;
             let has_time_remaining = true;
             let has_more_work = scheduled_host_callback(has_time_remaining, current_time);
             if has_more_work {
@@ -285,14 +289,50 @@ fn flush_work(has_time_remaining: bool, initial_time: f64) -> bool {
     }
 }
 
-pub fn unstable_should_yield_to_host() -> bool {
-    unsafe {
-        let time_elapsed = unstable_now() - START_TIME;
-        if time_elapsed < FRAME_YIELD_MS {
-            return false;
-        }
+// navigator.scheduling.isInputPending, only available in some browsers
+fn get_is_input_pending() -> Option<Function> {
+    let navigator = Reflect::get(&global(), &"navigator".into()).ok()?;
+    if navigator.is_undefined() || navigator.is_null() {
+        return None;
+    }
+    let scheduling = Reflect::get(&navigator, &"scheduling".into()).ok()?;
+    if scheduling.is_undefined() || scheduling.is_null() {
+        return None;
+    }
+    let is_input_pending = Reflect::get(&scheduling, &"isInputPending".into()).ok()?;
+    if !is_input_pending.is_function() {
+        return None;
     }
-    return true;
+    let is_input_pending = is_input_pending.dyn_into::<Function>().ok()?;
+    Some(is_input_pending.bind(&scheduling))
+}
+
+fn should_yield(
+    time_elapsed: f64,
+    needs_paint: bool,
+    is_input_pending: impl FnOnce() -> bool,
+) -> bool {
+    if time_elapsed < FRAME_YIELD_MS {
+        // still within the budget, yield early only if a paint was requested or there is pending input
+        return needs_paint || is_input_pending();
+    }
+    true
+}
+
+pub fn unstable_should_yield_to_host() -> bool {
+    let time_elapsed = unsafe { unstable_now() - START_TIME };
+    let needs_paint = unsafe { NEEDS_PAINT };
+    should_yield(time_elapsed, needs_paint, || match get_is_input_pending() {
+        Some(is_input_pending) => is_input_pending
+            .call0(&JsValue::null())
+            .map(|pending| pending.is_truthy())
+            .unwrap_or(false),
+        None => false,
+    })
+}
+
+pub fn unstable_request_paint() {
+    unsafe { NEEDS_PAINT = true }
 }
 
 pub fn unstable_run_with_priority(priority_level: Priority, event_handler: &Function) {
@@ -485,3 +525,37 @@ pub fn unstable_schedule_callback_no_delay(priority_level: Priority, callback: F
 pub fn unstable_get_current_priority_level() -> Priority {
     unsafe { CURRENT_PRIORITY_LEVEL.clone() }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{should_yield, FRAME_YIELD_MS};
+
+    #[test]
+    fn test_should_yield_on_pending_input() {
+        assert!(!should_yield(0.0, false, || false));
+        // pending input yields before the budget is exhausted
+        assert!(should_yield(0.0, false, || true));
+        assert!(should_yield(FRAME_YIELD_MS - 1.0, false, || true));
+    }
+
+    #[test]
+    fn test_should_yield_when_paint_requested() {
+        let mut called = false;
+        assert!(should_yield(0.0, true, || {
+            called = true;
+            false
+        }));
+        assert!(!called);
+    }
+
+    #[test]
+    fn test_should_yield_when_budget_exhausted() {
+        let mut called = false;
+        assert!(should_yield(FRAME_YIELD_MS, false, || {
+            called = true;
+            false
+        }));
+        // no need to check for input once the budget is exhausted
+        assert!(!called);
+    }
+}
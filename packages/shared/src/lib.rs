@@ -7,6 +7,31 @@ pub static REACT_CONTEXT_TYPE: &str = "react.context";
 pub static REACT_PROVIDER_TYPE: &str = "react.provider";
 pub static REACT_MEMO_TYPE: &str = "react.memo";
 pub static REACT_FRAGMENT_TYPE: &str = "react.fragment";
+pub static REACT_SUSPENSE_TYPE: &str = "react.suspense";
+pub static REACT_PORTAL_TYPE: &str = "react.portal";
+pub static REACT_SUSPENSE_LIST_TYPE: &str = "react.suspense_list";
+// TODO: only the name of Profiler is known, react doesn't export it and the reconciler has no
+// Profiler tag yet, so no render durations are measured or reported to onRender
+pub static REACT_PROFILER_TYPE: &str = "react.profiler";
+pub static REACT_STRICT_MODE_TYPE: &str = "react.strict_mode";
+pub static REACT_FORWARD_REF_TYPE: &str = "react.forward_ref";
+pub static REACT_LAZY_TYPE: &str = "react.lazy";
+// The _status of the payload of a lazy component once it has been loaded
+static LAZY_RESOLVED: f64 = 1.0;
+
+// The version of React that is implemented
+pub static REACT_VERSION: &str = "18.3.1";
+
+// The features supported by the renderers, (name, enabled)
+pub static FEATURE_FLAGS: [(&str, bool); 7] = [
+    ("concurrentRoot", true),
+    ("legacyRoot", true),
+    ("transitions", true),
+    ("deferredValue", true),
+    ("suspense", true),
+    ("portals", true),
+    ("serverRendering", false),
+];
 
 #[macro_export]
 macro_rules! log {
@@ -15,6 +40,13 @@ macro_rules! log {
     }
 }
 
+#[macro_export]
+macro_rules! error {
+    ( $( $t:tt )* ) => {
+        web_sys::console::error_1(&format!( $( $t )* ).into());
+    }
+}
+
 pub fn derive_from_js_value(js_value: &JsValue, str: &str) -> JsValue {
     match Reflect::get(&js_value, &JsValue::from_str(str)) {
         Ok(v) => v,
@@ -101,3 +133,91 @@ pub fn shallow_equal(a: &JsValue, b: &JsValue) -> bool {
 
     true
 }
+
+fn get_display_name(js_value: &JsValue) -> Option<String> {
+    if !js_value.is_object() && !js_value.is_function() {
+        return None;
+    }
+    derive_from_js_value(js_value, "displayName")
+        .as_string()
+        .filter(|name| !name.is_empty())
+}
+
+fn get_function_name(js_value: &JsValue) -> Option<String> {
+    get_display_name(js_value).or_else(|| {
+        derive_from_js_value(js_value, "name")
+            .as_string()
+            .filter(|name| !name.is_empty())
+    })
+}
+
+fn get_wrapped_name(outer_type: &JsValue, inner_type: &JsValue, wrapper_name: &str) -> String {
+    if let Some(display_name) = get_display_name(outer_type) {
+        return display_name;
+    }
+    match get_function_name(inner_type) {
+        Some(function_name) => format!("{}({})", wrapper_name, function_name),
+        None => wrapper_name.to_string(),
+    }
+}
+
+pub fn get_component_name_from_type(_type: &JsValue) -> Option<String> {
+    if _type.is_null() || _type.is_undefined() {
+        return None;
+    }
+
+    if _type.is_function() {
+        return get_function_name(_type);
+    }
+
+    if let Some(_type) = _type.as_string() {
+        let name = if _type == REACT_FRAGMENT_TYPE {
+            "Fragment"
+        } else if _type == REACT_SUSPENSE_TYPE {
+            "Suspense"
+        } else if _type == REACT_SUSPENSE_LIST_TYPE {
+            "SuspenseList"
+        } else if _type == REACT_PROFILER_TYPE {
+            "Profiler"
+        } else if _type == REACT_STRICT_MODE_TYPE {
+            "StrictMode"
+        } else {
+            // host component, such as div
+            return Some(_type);
+        };
+        return Some(name.to_string());
+    }
+
+    if !_type.is_object() {
+        return None;
+    }
+
+    let _typeof = derive_from_js_value(_type, "$$typeof").as_string()?;
+    if _typeof == REACT_CONTEXT_TYPE {
+        let name = get_display_name(_type).unwrap_or("Context".to_string());
+        Some(format!("{}.Consumer", name))
+    } else if _typeof == REACT_PROVIDER_TYPE {
+        let context = derive_from_js_value(_type, "_context");
+        let name = get_display_name(&context).unwrap_or("Context".to_string());
+        Some(format!("{}.Provider", name))
+    } else if _typeof == REACT_FORWARD_REF_TYPE {
+        let render = derive_from_js_value(_type, "render");
+        Some(get_wrapped_name(_type, &render, "ForwardRef"))
+    } else if _typeof == REACT_MEMO_TYPE {
+        get_display_name(_type)
+            .or_else(|| get_component_name_from_type(&derive_from_js_value(_type, "type")))
+            .or(Some("Memo".to_string()))
+    } else if _typeof == REACT_LAZY_TYPE {
+        // The name is only known once the lazy component has been resolved, _init isn't called
+        // since it would start loading it. The _result of a resolved payload is the module, its
+        // default export is the component
+        let payload = derive_from_js_value(_type, "_payload");
+        if derive_from_js_value(&payload, "_status").as_f64() != Some(LAZY_RESOLVED) {
+            return None;
+        }
+        let module = derive_from_js_value(&payload, "_result");
+        get_component_name_from_type(&derive_from_js_value(&module, "default"))
+    } else {
+        None
+    }
+}
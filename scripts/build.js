@@ -54,8 +54,9 @@ if (isTest) {
   fs.writeFileSync(
     reactNoopIndexFilename,
     (isTest
-      ? 'const {updateDispatcher} = require("react");\n'
-      : 'import {updateDispatcher} from "react";\n') + reactNoopIndexBgData
+      ? 'const {updateDispatcher, getCurrentTransition, startTransition} = require("react");\n'
+      : 'import {updateDispatcher, getCurrentTransition, startTransition} from "react";\n') +
+      reactNoopIndexBgData
   )
 }
 
@@ -67,20 +68,38 @@ const reactDomIndexBgData = fs.readFileSync(reactDomIndexFilename)
 fs.writeFileSync(
   reactDomIndexFilename,
   (isTest
-    ? 'const {updateDispatcher} = require("react");\n'
-    : 'import {updateDispatcher} from "react";\n') + reactDomIndexBgData
+    ? 'const {updateDispatcher, getCurrentTransition, startTransition} = require("react");\n'
+    : 'import {updateDispatcher, getCurrentTransition, startTransition} from "react";\n') +
+    reactDomIndexBgData
 )
 
-// add Fragment
-const reactIndexFilename = `${cwd}/dist/react/jsx-dev-runtime.js`
-const reactIndexData = fs.readFileSync(reactIndexFilename)
-fs.writeFileSync(
-  reactIndexFilename,
-  reactIndexData + `export const Fragment='react.fragment';\n`
-)
-const reactTsIndexFilename = `${cwd}/dist/react/jsx-dev-runtime.d.ts`
-const reactTsIndexData = fs.readFileSync(reactTsIndexFilename)
-fs.writeFileSync(
-  reactTsIndexFilename,
-  reactTsIndexData + `export const Fragment: string;\n`
-)
+// add Fragment, Suspense, StrictMode
+const reactSymbols = {
+  Fragment: 'react.fragment',
+  Suspense: 'react.suspense',
+  StrictMode: 'react.strict_mode'
+}
+for (const outName of ['jsx-dev-runtime', 'index']) {
+  const reactIndexFilename = `${cwd}/dist/react/${outName}.js`
+  const reactIndexData = fs.readFileSync(reactIndexFilename)
+  fs.writeFileSync(
+    reactIndexFilename,
+    reactIndexData +
+      Object.entries(reactSymbols)
+        .map(([name, value]) =>
+          isTest
+            ? `module.exports.${name}='${value}';\n`
+            : `export const ${name}='${value}';\n`
+        )
+        .join('')
+  )
+  const reactTsIndexFilename = `${cwd}/dist/react/${outName}.d.ts`
+  const reactTsIndexData = fs.readFileSync(reactTsIndexFilename)
+  fs.writeFileSync(
+    reactTsIndexFilename,
+    reactTsIndexData +
+      Object.keys(reactSymbols)
+        .map((name) => `export const ${name}: string;\n`)
+        .join('')
+  )
+}